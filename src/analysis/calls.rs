@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use swc_common::Span;
+use swc_ecma_ast::{
+    CallExpr, Callee, Expr, ExprOrSpread, ImportSpecifier, ModuleDecl,
+    ModuleItem, OptChainBase, OptChainExpr,
+};
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+use crate::utils;
+
+#[derive(Clone)]
+pub struct CallsRequest {
+    pub path: PathBuf,
+    pub name: String,
+    pub arguments: Vec<String>,
+    pub arg_count: Option<usize>,
+    pub source: Option<String>,
+}
+
+impl ProcessorRequest for CallsRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        // When the call must be bound to an import source, find the local
+        // bindings imported from that source first. Files which don't import
+        // from the source can be skipped entirely.
+        let bindings = match &self.source {
+            Some(source) => {
+                let bindings = get_import_bindings(parsed, source);
+                if bindings.is_empty() {
+                    return vec![];
+                }
+
+                Some(bindings)
+            }
+            None => None,
+        };
+
+        let mut visitor =
+            CallVisitor { calls: Vec::new(), request: self, bindings };
+
+        visitor.visit_module(&parsed.module);
+        visitor.calls.into_iter().map(Match::from).collect()
+    }
+}
+
+struct CallVisitor<'a> {
+    calls: Vec<Span>,
+    request: &'a CallsRequest,
+    /// Maps local binding names to the imported name when the request is bound
+    /// to an import source.
+    bindings: Option<HashMap<String, String>>,
+}
+
+impl<'a> CallVisitor<'a> {
+    fn check(&mut self, span: Span, callee: &Expr, args: &[ExprOrSpread]) {
+        let name = match get_callee_name(callee, &self.bindings) {
+            Some(name) => name,
+            None => return,
+        };
+
+        if name == self.request.name
+            && has_arg_count(args, self.request.arg_count)
+            && has_required_args(args, &self.request.arguments)
+        {
+            self.calls.push(span);
+        }
+    }
+}
+
+impl<'a> Visit for CallVisitor<'a> {
+    fn visit_call_expr(&mut self, node: &CallExpr) {
+        if let Callee::Expr(callee) = &node.callee {
+            self.check(node.span, callee, &node.args);
+        }
+
+        node.visit_children_with(self)
+    }
+
+    fn visit_opt_chain_expr(&mut self, node: &OptChainExpr) {
+        if let OptChainBase::Call(call) = &*node.base {
+            self.check(node.span, &call.callee, &call.args);
+        }
+
+        node.visit_children_with(self)
+    }
+}
+
+/// Get the local bindings created by imports from the given source. Named
+/// imports map to the imported name so aliased imports still match the
+/// requested name, while default and namespace imports keep their local name.
+fn get_import_bindings(
+    parsed: &ParsedModule,
+    source: &str,
+) -> HashMap<String, String> {
+    parsed
+        .module
+        .body
+        .iter()
+        .filter_map(|item| match item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => Some(import),
+            _ => None,
+        })
+        .filter(|import| import.src.value == *source)
+        .flat_map(|import| import.specifiers.iter())
+        .map(|specifier| match specifier {
+            ImportSpecifier::Named(named) => {
+                let local = utils::ast::get_ident_name(&named.local);
+                let imported = named
+                    .imported
+                    .as_ref()
+                    .map_or(local.clone(), utils::ast::get_export_name);
+
+                (local, imported)
+            }
+            ImportSpecifier::Default(default) => {
+                let local = utils::ast::get_ident_name(&default.local);
+                (local.clone(), local)
+            }
+            ImportSpecifier::Namespace(namespace) => {
+                let local = utils::ast::get_ident_name(&namespace.local);
+                (local.clone(), local)
+            }
+        })
+        .collect()
+}
+
+/// Get the name of the callee. When bindings are provided, the root of the
+/// callee must be one of the bindings and is replaced with its imported name.
+fn get_callee_name(
+    callee: &Expr,
+    bindings: &Option<HashMap<String, String>>,
+) -> Option<String> {
+    let name = utils::ast::get_expr_path(callee)?;
+
+    match bindings {
+        Some(bindings) => {
+            let (root, rest) = match name.split_once('.') {
+                Some((root, rest)) => (root, Some(rest)),
+                None => (name.as_str(), None),
+            };
+
+            let imported = bindings.get(root)?;
+            Some(match rest {
+                Some(rest) => format!("{}.{}", imported, rest),
+                None => imported.to_string(),
+            })
+        }
+        None => Some(name),
+    }
+}
+
+fn has_arg_count(args: &[ExprOrSpread], count: Option<usize>) -> bool {
+    match count {
+        Some(count) => args.len() == count,
+        None => true,
+    }
+}
+
+/// Check that the literal arguments match the required values by position. A
+/// value of `_` matches any argument.
+fn has_required_args(args: &[ExprOrSpread], values: &[String]) -> bool {
+    values.iter().enumerate().all(|(index, value)| {
+        let arg = match args.get(index) {
+            Some(arg) if arg.spread.is_none() => arg,
+            _ => return false,
+        };
+
+        if value == "_" {
+            return true;
+        }
+
+        match &*arg.expr {
+            Expr::Lit(lit) => utils::ast::get_lit_value(lit) == *value,
+            Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl
+                .quasis
+                .first()
+                .is_some_and(|quasi| &*quasi.raw == value.as_str()),
+            _ => false,
+        }
+    })
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobMatcher};
+use swc_common::Loc;
+
+use crate::analysis::imports::ModuleImport;
+use crate::config::{Boundary, Config, Rule, Severity};
+use crate::graph::package::{self, Packages};
+use crate::graph::resolver::{self, Resolver};
+use crate::graph::ModuleGraph;
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+
+/// A boundary with its globs compiled
+#[derive(Clone)]
+struct CompiledBoundary {
+    from: GlobMatcher,
+    to: GlobMatcher,
+    boundary: Boundary,
+}
+
+/// A rule with its source glob compiled
+#[derive(Clone)]
+struct CompiledRule {
+    source: GlobMatcher,
+    rule: Rule,
+}
+
+impl CompiledRule {
+    fn is_match(&self, import: &ModuleImport) -> bool {
+        self.source.is_match(&import.source)
+            && self.rule.kind.is_none_or(|kind| kind == import.kind)
+            && self
+                .rule
+                .specifier
+                .as_ref()
+                .is_none_or(|specifier| import.names.contains(specifier))
+    }
+}
+
+#[derive(Clone)]
+pub struct CheckRequest {
+    pub path: PathBuf,
+    /// The absolute path of the directory boundary globs are relative to
+    pub root: PathBuf,
+    boundaries: Vec<CompiledBoundary>,
+    rules: Vec<CompiledRule>,
+    graph: ModuleGraph,
+    packages: Packages,
+}
+
+impl CheckRequest {
+    pub fn new(
+        path: PathBuf,
+        config: &Config,
+        root: PathBuf,
+        resolver: Resolver,
+    ) -> Result<CheckRequest, globset::Error> {
+        let boundaries = config
+            .boundaries
+            .iter()
+            .cloned()
+            .map(|boundary| {
+                Ok(CompiledBoundary {
+                    from: Glob::new(&boundary.from)?.compile_matcher(),
+                    to: Glob::new(&boundary.to)?.compile_matcher(),
+                    boundary,
+                })
+            })
+            .collect::<Result<_, globset::Error>>()?;
+
+        let rules = config
+            .rules
+            .iter()
+            .cloned()
+            .map(|rule| {
+                Ok(CompiledRule {
+                    source: Glob::new(&rule.source)?.compile_matcher(),
+                    rule,
+                })
+            })
+            .collect::<Result<_, globset::Error>>()?;
+
+        Ok(CheckRequest {
+            path,
+            root,
+            boundaries,
+            rules,
+            graph: ModuleGraph::new(resolver),
+            packages: Packages::default(),
+        })
+    }
+
+    /// Find the first boundary matching an import and get the message to
+    /// report if the import is denied.
+    fn check(&mut self, from: &Path, to: &Path) -> Option<String> {
+        let relative_from = from.strip_prefix(&self.root).unwrap_or(from);
+        let relative_to = to.strip_prefix(&self.root).unwrap_or(to);
+
+        let is_cross_package = {
+            let from_package = self.packages.find(from).map(|p| p.dir.clone());
+            let to_package = self.packages.find(to).map(|p| p.dir.clone());
+
+            from_package != to_package
+        };
+
+        let boundary = self.boundaries.iter().find(|compiled| {
+            compiled.from.is_match(relative_from)
+                && compiled.to.is_match(relative_to)
+                && (!compiled.boundary.cross_package || is_cross_package)
+        })?;
+
+        if boundary.boundary.allow {
+            return None;
+        }
+
+        Some(boundary.boundary.message.clone().unwrap_or_else(|| {
+            format!(
+                "{} may not import {}",
+                relative_from.display(),
+                relative_to.display()
+            )
+        }))
+    }
+}
+
+impl ProcessorRequest for CheckRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn uses_all_modules(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        let module = self.graph.add(parsed);
+
+        // Rules only depend on the module itself, so they are all checked in
+        // a single pass over its imports as it is added to the graph.
+        let node = &self.graph.modules[module];
+        let mut matches = Vec::new();
+
+        for import in &node.imports {
+            for compiled in self.rules.iter().filter(|r| r.is_match(import)) {
+                matches.push(Match {
+                    span: import.span,
+                    text: node.locate(import.span, None).map(|(text, _)| {
+                        format!(
+                            "{}: {}: {}",
+                            compiled.rule.severity, compiled.rule.message, text
+                        )
+                    }),
+                    severity: compiled.rule.severity,
+                });
+            }
+        }
+
+        matches
+    }
+
+    fn finish(&mut self) -> Vec<(String, Loc)> {
+        // The graph is taken while checking imports since `check` borrows the
+        // request, and put back for the next run when watching files.
+        let graph = std::mem::take(&mut self.graph);
+        let workspace: HashMap<String, PathBuf> = graph
+            .modules
+            .iter()
+            .filter_map(|module| {
+                let package = self.packages.find(&module.path)?;
+                Some((package.manifest.name.clone()?, package.dir.clone()))
+            })
+            .collect();
+
+        let mut results = Vec::new();
+
+        for module in graph.sorted() {
+            let node = &graph.modules[module];
+
+            for import in &node.imports {
+                let target = match resolve_import(
+                    &graph.resolver,
+                    &workspace,
+                    &node.path,
+                    &import.source,
+                ) {
+                    Some(target) => target,
+                    None => continue,
+                };
+
+                if let Some(message) = self.check(&node.path, &target) {
+                    results.extend(node.locate(import.span, None).map(
+                        |(text, loc)| {
+                            (
+                                format!(
+                                    "{}: {}: {}",
+                                    Severity::Error,
+                                    message,
+                                    text
+                                ),
+                                loc,
+                            )
+                        },
+                    ));
+                }
+            }
+        }
+
+        self.graph = graph;
+        results
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.graph.remove(path);
+        self.packages = Packages::default();
+    }
+}
+
+/// Resolve an import to the path of the imported module. Bare imports of
+/// other packages in the project resolve to the package directory, so
+/// deep imports such as `@scope/pkg/src/a` are checked as well.
+fn resolve_import(
+    resolver: &Resolver,
+    workspace: &HashMap<String, PathBuf>,
+    from: &Path,
+    source: &str,
+) -> Option<PathBuf> {
+    if let Some(path) = resolver.resolve(from, source) {
+        return Some(path);
+    }
+
+    if resolver::is_relative(source) {
+        return None;
+    }
+
+    let name = package::package_name(source);
+    let dir = workspace.get(name)?;
+    let path = dir.join(source[name.len()..].trim_start_matches('/'));
+
+    Some(resolver::resolve_path(&path).unwrap_or(path))
+}
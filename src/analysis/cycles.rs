@@ -0,0 +1,294 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use swc_common::Loc;
+
+use crate::analysis::imports::ImportKind;
+use crate::graph::resolver::Resolver;
+use crate::graph::ModuleGraph;
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+
+#[derive(Clone)]
+pub struct CyclesRequest {
+    pub path: PathBuf,
+    /// Ignore type only imports since they are erased at runtime
+    pub ignore_type_imports: bool,
+    graph: ModuleGraph,
+}
+
+impl CyclesRequest {
+    pub fn new(
+        path: PathBuf,
+        ignore_type_imports: bool,
+        resolver: Resolver,
+    ) -> CyclesRequest {
+        CyclesRequest {
+            path,
+            ignore_type_imports,
+            graph: ModuleGraph::new(resolver),
+        }
+    }
+
+    /// Build the adjacency list of the import graph. Each edge contains the
+    /// index of the first import of the target module. Dynamic imports are
+    /// evaluated lazily, so they can't cause cycles during initialization.
+    fn edges(&self) -> Vec<Vec<(usize, usize)>> {
+        (0..self.graph.modules.len())
+            .map(|module| {
+                let imports = &self.graph.modules[module].imports;
+                let mut targets = HashSet::new();
+
+                self.graph
+                    .dependencies(module)
+                    .filter(|(_, index)| {
+                        let import = &imports[*index];
+
+                        import.kind != ImportKind::Dynamic
+                            && !(self.ignore_type_imports && import.type_only)
+                    })
+                    .filter(|(target, _)| targets.insert(*target))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl ProcessorRequest for CyclesRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn uses_all_modules(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        self.graph.add(parsed);
+        vec![]
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.graph.remove(path);
+    }
+
+    fn finish(&mut self) -> Vec<(String, Loc)> {
+        let edges = self.edges();
+        let order = self.graph.sorted();
+        let rank: HashMap<usize, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(rank, module)| (*module, rank))
+            .collect();
+
+        // Only components with more than one module or a module importing
+        // itself contain a cycle.
+        let mut components: Vec<Vec<usize>> = find_components(&edges)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || edges[component[0]]
+                        .iter()
+                        .any(|(target, _)| *target == component[0])
+            })
+            .map(|mut component| {
+                component.sort_by_key(|module| rank[module]);
+                component
+            })
+            .collect();
+
+        components.sort_by_key(|component| rank[&component[0]]);
+
+        components
+            .iter()
+            .enumerate()
+            .flat_map(|(index, component)| {
+                get_chain(&edges, component)
+                    .into_iter()
+                    .filter_map(|(module, import)| {
+                        let node = &self.graph.modules[module];
+                        let (text, loc) =
+                            node.locate(node.imports[import].span, None)?;
+
+                        Some((format!("cycle {}: {}", index + 1, text), loc))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Find the strongly connected components of the graph using Tarjan's
+/// algorithm.
+fn find_components(edges: &[Vec<(usize, usize)>]) -> Vec<Vec<usize>> {
+    let mut tarjan = Tarjan {
+        edges,
+        index: 0,
+        indices: vec![None; edges.len()],
+        low: vec![0; edges.len()],
+        stack: Vec::new(),
+        on_stack: vec![false; edges.len()],
+        components: Vec::new(),
+    };
+
+    for module in 0..edges.len() {
+        if tarjan.indices[module].is_none() {
+            tarjan.connect(module);
+        }
+    }
+
+    tarjan.components
+}
+
+struct Tarjan<'a> {
+    edges: &'a [Vec<(usize, usize)>],
+    index: usize,
+    indices: Vec<Option<usize>>,
+    low: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    components: Vec<Vec<usize>>,
+}
+
+impl<'a> Tarjan<'a> {
+    /// Visit the modules reachable from a module. The depth-first search keeps
+    /// its own stack of modules and the index of their next edge, since import
+    /// chains in large projects can be deep enough to overflow the call stack.
+    fn connect(&mut self, module: usize) {
+        let mut path = vec![(module, 0)];
+        self.visit(module);
+
+        while let Some((module, edge)) = path.last_mut() {
+            let module = *module;
+
+            if let Some((target, _)) = self.edges[module].get(*edge) {
+                *edge += 1;
+
+                match self.indices[*target] {
+                    None => {
+                        self.visit(*target);
+                        path.push((*target, 0));
+                    }
+                    Some(index) if self.on_stack[*target] => {
+                        self.low[module] = self.low[module].min(index);
+                    }
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            path.pop();
+
+            if let Some((parent, _)) = path.last() {
+                self.low[*parent] = self.low[*parent].min(self.low[module]);
+            }
+
+            if Some(self.low[module]) == self.indices[module] {
+                let mut component = Vec::new();
+
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+
+                    if member == module {
+                        break;
+                    }
+                }
+
+                self.components.push(component);
+            }
+        }
+    }
+
+    fn visit(&mut self, module: usize) {
+        self.indices[module] = Some(self.index);
+        self.low[module] = self.index;
+        self.index += 1;
+        self.stack.push(module);
+        self.on_stack[module] = true;
+    }
+}
+
+/// Get a chain of imports starting and ending at the first module of the
+/// component which passes through every module in the component. Each link of
+/// the chain is the importing module and the index of its import.
+fn get_chain(
+    edges: &[Vec<(usize, usize)>],
+    component: &[usize],
+) -> Vec<(usize, usize)> {
+    let members: HashSet<usize> = component.iter().copied().collect();
+    let start = component[0];
+    let mut remaining: HashSet<usize> =
+        component[1..].iter().copied().collect();
+    let mut current = start;
+    let mut links = Vec::new();
+
+    while !remaining.is_empty() {
+        let path = find_path(edges, &members, current, |module| {
+            remaining.contains(&module)
+        });
+
+        let last = match path.last() {
+            Some((_, to, _)) => *to,
+            None => break,
+        };
+
+        path.iter().for_each(|(_, to, _)| {
+            remaining.remove(to);
+        });
+
+        current = last;
+        links.extend(path);
+    }
+
+    links.extend(find_path(edges, &members, current, |module| module == start));
+    links.into_iter().map(|(from, _, import)| (from, import)).collect()
+}
+
+/// Find the shortest path from a module to a target module without leaving
+/// the component. Each link of the path contains the importing module, the
+/// imported module and the index of the import.
+fn find_path<F>(
+    edges: &[Vec<(usize, usize)>],
+    members: &HashSet<usize>,
+    from: usize,
+    is_target: F,
+) -> Vec<(usize, usize, usize)>
+where
+    F: Fn(usize) -> bool,
+{
+    let mut parents: HashMap<usize, (usize, usize)> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    let mut visited = HashSet::from([from]);
+
+    while let Some(module) = queue.pop_front() {
+        for (target, import) in &edges[module] {
+            if !members.contains(target) {
+                continue;
+            }
+
+            // Targets are checked before visited modules since the path may
+            // lead back to the module it started from.
+            if is_target(*target) {
+                let mut path = vec![(module, *target, *import)];
+                let mut current = module;
+
+                while let Some((parent, import)) = parents.get(&current) {
+                    path.push((*parent, current, *import));
+                    current = *parent;
+                }
+
+                path.reverse();
+                return path;
+            }
+
+            if visited.insert(*target) {
+                parents.insert(*target, (module, *import));
+                queue.push_back(*target);
+            }
+        }
+    }
+
+    vec![]
+}
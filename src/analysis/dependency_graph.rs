@@ -0,0 +1,316 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use swc_common::Loc;
+
+use crate::graph::package::{self, Packages};
+use crate::graph::resolver::{self, Resolver};
+use crate::graph::ModuleGraph;
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+use crate::utils;
+
+/// The diagram language of graphs printed by the console output formats
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Diagram {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum GraphFormat {
+    Diagram(Diagram),
+    /// JSON object mapping each node to the nodes it imports
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Granularity {
+    /// Each module is a node
+    File,
+    /// Modules are collapsed into their directory
+    Directory,
+    /// Modules are collapsed into the package containing them
+    Package,
+}
+
+#[derive(Clone)]
+pub struct GraphRequest {
+    pub path: PathBuf,
+    pub format: GraphFormat,
+    pub granularity: Granularity,
+    /// Only include modules reachable from this module
+    pub root: Option<PathBuf>,
+    /// The maximum number of imports to follow from the root module
+    pub depth: Option<usize>,
+    /// The canonical paths of the changed files. When set, only the imports
+    /// of changed modules are included.
+    pub changed: Option<HashSet<PathBuf>>,
+    graph: ModuleGraph,
+    packages: Packages,
+}
+
+impl GraphRequest {
+    pub fn new(
+        path: PathBuf,
+        format: GraphFormat,
+        granularity: Granularity,
+        root: Option<PathBuf>,
+        depth: Option<usize>,
+        changed: Option<HashSet<PathBuf>>,
+        resolver: Resolver,
+    ) -> GraphRequest {
+        GraphRequest {
+            path,
+            format,
+            granularity,
+            root,
+            depth,
+            changed,
+            graph: ModuleGraph::new(resolver),
+            packages: Packages::default(),
+        }
+    }
+
+    /// Get the imported modules of each module. In package granularity, bare
+    /// imports of other packages in the project are also included since they
+    /// aren't resolved to a module.
+    fn edges(&mut self) -> Vec<Vec<usize>> {
+        let workspace: HashMap<String, usize> = match self.granularity {
+            Granularity::Package => (0..self.graph.modules.len())
+                .filter_map(|module| {
+                    let path = &self.graph.modules[module].path;
+                    let name =
+                        self.packages.find(path)?.manifest.name.clone()?;
+
+                    Some((name, module))
+                })
+                .collect(),
+            _ => HashMap::new(),
+        };
+
+        (0..self.graph.modules.len())
+            .map(|module| {
+                let node = &self.graph.modules[module];
+                let mut targets: Vec<usize> = self
+                    .graph
+                    .dependencies(module)
+                    .map(|(target, _)| target)
+                    .collect();
+
+                targets.extend(
+                    node.imports
+                        .iter()
+                        .filter(|import| !resolver::is_relative(&import.source))
+                        .filter_map(|import| {
+                            workspace.get(package::package_name(&import.source))
+                        }),
+                );
+
+                targets
+            })
+            .collect()
+    }
+
+    /// Find the modules to include in the graph. When there is a root module,
+    /// only modules within the depth limit are included along with the depth
+    /// of each module.
+    fn reachable(&self, edges: &[Vec<usize>]) -> HashMap<usize, usize> {
+        let root = match &self.root {
+            Some(root) => utils::absolutize(&self.path.join(root)),
+            None => {
+                return (0..edges.len()).map(|module| (module, 0)).collect()
+            }
+        };
+
+        let start = match self.graph.modules.iter().position(|m| m.path == root)
+        {
+            Some(start) => start,
+            None => return HashMap::new(),
+        };
+
+        let mut depths = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(module) = queue.pop_front() {
+            let depth = depths[&module];
+            if self.depth.is_some_and(|limit| depth >= limit) {
+                continue;
+            }
+
+            for target in &edges[module] {
+                if !depths.contains_key(target) {
+                    depths.insert(*target, depth + 1);
+                    queue.push_back(*target);
+                }
+            }
+        }
+
+        depths
+    }
+
+    fn is_changed(&self, module: usize) -> bool {
+        match &self.changed {
+            Some(changed) => self.graph.modules[module]
+                .path
+                .canonicalize()
+                .is_ok_and(|path| changed.contains(&path)),
+            None => true,
+        }
+    }
+
+    /// Get the label of the node a module is collapsed into
+    fn label(&mut self, module: usize) -> String {
+        let root = utils::absolutize(&self.path);
+        let path = self.graph.modules[module].path.clone();
+        let relative = |path: &Path| {
+            let path = path.strip_prefix(&root).unwrap_or(path);
+
+            match path.to_string_lossy() {
+                path if path.is_empty() => String::from("."),
+                path => path.to_string(),
+            }
+        };
+
+        match self.granularity {
+            Granularity::File => relative(&path),
+            Granularity::Directory => {
+                relative(path.parent().unwrap_or(Path::new("")))
+            }
+            Granularity::Package => match self.packages.find(&path) {
+                Some(package) => match &package.manifest.name {
+                    Some(name) => name.clone(),
+                    None => relative(&package.dir),
+                },
+                None => String::from("."),
+            },
+        }
+    }
+}
+
+impl ProcessorRequest for GraphRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn uses_all_modules(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        self.graph.add(parsed);
+        vec![]
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.graph.remove(path);
+        self.packages = Packages::default();
+    }
+
+    fn finish(&mut self) -> Vec<(String, Loc)> {
+        let edges = self.edges();
+        let reachable = self.reachable(&edges);
+        let labels: HashMap<usize, String> = reachable
+            .keys()
+            .map(|module| (*module, self.label(*module)))
+            .collect();
+
+        let mut nodes: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+
+        // When only reporting changes, unchanged modules are only included
+        // when imported by a changed module.
+        for (module, depth) in &reachable {
+            if !self.is_changed(*module) {
+                continue;
+            }
+
+            let label = labels[module].as_str();
+            nodes.entry(label).or_default();
+
+            // Modules at the depth limit are included without their imports
+            if self.depth.is_some_and(|limit| *depth >= limit) {
+                continue;
+            }
+
+            for target in edges[*module]
+                .iter()
+                .filter_map(|target| labels.get(target))
+                .filter(|target| *target != label)
+            {
+                nodes.entry(label).or_default().insert(target);
+                nodes.entry(target).or_default();
+            }
+        }
+
+        // The graph is a single document rather than a list of matches, so it
+        // is printed directly instead of using the logger.
+        print!(
+            "{}",
+            match self.format {
+                GraphFormat::Diagram(Diagram::Dot) => format_dot(&nodes),
+                GraphFormat::Diagram(Diagram::Mermaid) => {
+                    format_mermaid(&nodes)
+                }
+                GraphFormat::Json => format_json(&nodes),
+            }
+        );
+
+        vec![]
+    }
+}
+
+fn quote(label: &str) -> String {
+    format!("\"{}\"", label.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn format_dot(nodes: &BTreeMap<&str, BTreeSet<&str>>) -> String {
+    let mut output = String::from("digraph {\n");
+
+    for (node, targets) in nodes {
+        if targets.is_empty() {
+            output.push_str(&format!("  {};\n", quote(node)));
+        }
+
+        for target in targets {
+            output.push_str(&format!(
+                "  {} -> {};\n",
+                quote(node),
+                quote(target)
+            ));
+        }
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+fn format_mermaid(nodes: &BTreeMap<&str, BTreeSet<&str>>) -> String {
+    let ids: HashMap<&str, usize> =
+        nodes.keys().enumerate().map(|(id, node)| (*node, id)).collect();
+    let mut output = String::from("graph LR\n");
+
+    for (node, id) in nodes.keys().map(|node| (node, ids[node])) {
+        output.push_str(&format!(
+            "  n{}[\"{}\"]\n",
+            id,
+            node.replace('"', "#quot;")
+        ));
+    }
+
+    for (node, targets) in nodes {
+        for target in targets {
+            output
+                .push_str(&format!("  n{} --> n{}\n", ids[node], ids[target]));
+        }
+    }
+
+    output
+}
+
+fn format_json(nodes: &BTreeMap<&str, BTreeSet<&str>>) -> String {
+    let mut output = serde_json::to_string_pretty(nodes).unwrap_or_default();
+    output.push('\n');
+    output
+}
@@ -0,0 +1,281 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use globset::GlobSet;
+use swc_common::{BytePos, Loc, SourceMap};
+
+use crate::graph::package::{self, Package, Packages};
+use crate::graph::resolver::{self, Resolver};
+use crate::graph::ModuleGraph;
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+use crate::utils;
+
+/// Node.js builtin modules which can be imported without the `node:` prefix
+const BUILTINS: [&str; 41] = [
+    "assert",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "console",
+    "constants",
+    "crypto",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "domain",
+    "events",
+    "fs",
+    "http",
+    "http2",
+    "https",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "punycode",
+    "querystring",
+    "readline",
+    "repl",
+    "stream",
+    "string_decoder",
+    "sys",
+    "timers",
+    "tls",
+    "trace_events",
+    "tty",
+    "url",
+    "util",
+    "v8",
+    "vm",
+    "worker_threads",
+    "zlib",
+];
+
+#[derive(Clone)]
+pub struct DepsRequest {
+    pub path: PathBuf,
+    /// Modules which are allowed to import dev dependencies, such as tests
+    pub dev_files: GlobSet,
+    /// The absolute path of the directory dev file globs are relative to
+    pub root: PathBuf,
+    graph: ModuleGraph,
+    packages: Packages,
+}
+
+impl DepsRequest {
+    pub fn new(
+        path: PathBuf,
+        dev_files: GlobSet,
+        root: PathBuf,
+        resolver: Resolver,
+    ) -> DepsRequest {
+        DepsRequest {
+            path,
+            dev_files,
+            root,
+            graph: ModuleGraph::new(resolver),
+            packages: Packages::default(),
+        }
+    }
+
+    fn is_dev_file(&self, module: usize) -> bool {
+        let path = &self.graph.modules[module].path;
+        self.dev_files.is_match(path.strip_prefix(&self.root).unwrap_or(path))
+    }
+}
+
+impl ProcessorRequest for DepsRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn uses_all_modules(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        self.graph.add(parsed);
+        vec![]
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.graph.remove(path);
+
+        // Manifests aren't watched, so they are read again after any change
+        // in case they were edited too.
+        self.packages = Packages::default();
+    }
+
+    fn finish(&mut self) -> Vec<(String, Loc)> {
+        // Group the modules by the package they belong to. Modules outside of
+        // any package or in a package with an invalid manifest can't be
+        // audited.
+        let mut packages: BTreeMap<PathBuf, (Rc<Package>, Vec<usize>)> =
+            BTreeMap::new();
+
+        for module in self.graph.sorted() {
+            if let Some(package) = self
+                .packages
+                .find(&self.graph.modules[module].path)
+                .filter(|package| package.is_valid)
+            {
+                packages
+                    .entry(package.dir.clone())
+                    .or_insert_with(|| (package.clone(), Vec::new()))
+                    .1
+                    .push(module);
+            }
+        }
+
+        packages
+            .values()
+            .flat_map(|(package, modules)| self.audit(package, modules))
+            .collect()
+    }
+
+    fn errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.packages.errors)
+    }
+}
+
+impl DepsRequest {
+    fn audit(
+        &self,
+        package: &Package,
+        modules: &[usize],
+    ) -> Vec<(String, Loc)> {
+        let manifest = &package.manifest;
+        let mut imported = HashSet::new();
+        let mut results = Vec::new();
+
+        for module in modules {
+            let node = &self.graph.modules[*module];
+            let is_dev_file = self.is_dev_file(*module);
+
+            for import in &node.imports {
+                // Bare specifiers can be path aliases or resolve from the
+                // `baseUrl` of the tsconfig, in which case they are modules of
+                // the project rather than packages.
+                if !is_package_import(&import.source)
+                    || self
+                        .graph
+                        .resolver
+                        .resolve(&node.path, &import.source)
+                        .is_some()
+                {
+                    continue;
+                }
+
+                let name = package::package_name(&import.source);
+                imported.insert(name.to_string());
+
+                // Packages can import themselves by name
+                if manifest.name.as_deref() == Some(name) {
+                    continue;
+                }
+
+                let is_declared = |name: &str| {
+                    manifest.dependencies.contains_key(name)
+                        || manifest.peer_dependencies.contains_key(name)
+                        || manifest.optional_dependencies.contains_key(name)
+                };
+
+                let problem = if is_declared(name) {
+                    continue;
+                } else if manifest.dev_dependencies.contains_key(name) {
+                    // Type only imports are erased at runtime, so they can
+                    // use dev dependencies.
+                    if import.type_only || is_dev_file {
+                        continue;
+                    }
+
+                    "dev dependency"
+                } else if import.type_only
+                    && manifest
+                        .dev_dependencies
+                        .contains_key(&types_package(name))
+                {
+                    continue;
+                } else {
+                    "missing dependency"
+                };
+
+                if let Some((text, loc)) = node.locate(import.span, None) {
+                    results.push((
+                        format!("{} \"{}\": {}", problem, name, text),
+                        loc,
+                    ));
+                }
+            }
+        }
+
+        let unused: Vec<&String> = manifest
+            .dependencies
+            .keys()
+            .filter(|name| !imported.contains(*name))
+            .collect();
+
+        if !unused.is_empty() {
+            // Report the manifest relative to the working directory like the
+            // paths of modules.
+            let cwd = utils::absolutize(&self.path);
+            let dir = match package.dir.strip_prefix(&cwd) {
+                Ok(relative) => self.path.join(relative),
+                Err(_) => package.dir.clone(),
+            };
+
+            results.extend(locate_unused(&dir.join("package.json"), &unused));
+        }
+
+        results
+    }
+}
+
+/// Check if an import source refers to a package rather than a file, a
+/// builtin module or a module using a protocol such as `node:`.
+fn is_package_import(source: &str) -> bool {
+    !resolver::is_relative(source)
+        && !source.starts_with('#')
+        && !source.contains(':')
+        && !BUILTINS.contains(&package::package_name(source))
+}
+
+/// Get the name of the DefinitelyTyped package for a package (e.g., `@types/react`
+/// for `react` and `@types/scope__pkg` for `@scope/pkg`).
+fn types_package(name: &str) -> String {
+    match name.strip_prefix('@') {
+        Some(scoped) => format!("@types/{}", scoped.replace('/', "__")),
+        None => format!("@types/{}", name),
+    }
+}
+
+/// Locate unused dependencies in the `package.json` file of a package. The
+/// manifest isn't a module, so it is loaded into its own source map to find
+/// the line of each dependency.
+fn locate_unused(path: &Path, names: &[&String]) -> Vec<(String, Loc)> {
+    let source_map = SourceMap::default();
+    let file = match source_map.load_file(path) {
+        Ok(file) => file,
+        Err(_) => return vec![],
+    };
+
+    let start = file.src.find("\"dependencies\"").unwrap_or(0);
+
+    names
+        .iter()
+        .map(|name| {
+            let key = format!("\"{}\"", name);
+            let offset = file.src[start..].find(&key).map_or(0, |i| start + i);
+            let loc = source_map
+                .lookup_char_pos(file.start_pos + BytePos(offset as u32));
+
+            (format!("unused dependency \"{}\"", name), loc)
+        })
+        .collect()
+}
@@ -0,0 +1,237 @@
+use std::path::PathBuf;
+
+use swc_common::Span;
+use swc_ecma_ast::{
+    Decl, DefaultDecl, ExportSpecifier, Expr, Module, ModuleDecl, ModuleItem,
+    TsModuleName,
+};
+
+use crate::config::Severity;
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+use crate::utils;
+
+#[derive(Clone)]
+pub struct ExportsRequest {
+    pub path: PathBuf,
+}
+
+impl ProcessorRequest for ExportsRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        get_exports(&parsed.module)
+            .into_iter()
+            .map(|export| Match {
+                span: export.span,
+                text: Some(export.to_string()),
+                severity: Severity::Error,
+            })
+            .collect()
+    }
+}
+
+/// An export of a module
+#[derive(Clone)]
+pub struct Export {
+    pub span: Span,
+    /// The name the binding is exported as. Default exports are named
+    /// `default`, and `export =` assignments are named `=`.
+    pub name: String,
+    pub kind: ExportKind,
+    pub type_only: bool,
+}
+
+/// What an export refers to
+#[derive(Clone)]
+pub enum ExportKind {
+    /// A binding declared in the module (e.g., `export { a as b }`)
+    Local(String),
+    /// An expression without a name (e.g., `export default {}`)
+    Expr,
+    /// A binding re-exported from another module. Namespace re-exports such
+    /// as `export * as ns from "a"` are named `*`.
+    ReExport { source: String, name: String },
+    /// All named exports of another module (e.g., `export * from "a"`)
+    ReExportAll { source: String },
+}
+
+impl std::fmt::Display for Export {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.type_only {
+            write!(f, "type ")?;
+        }
+
+        match &self.kind {
+            ExportKind::Local(local) if *local == self.name => {
+                write!(f, "{}", self.name)
+            }
+            ExportKind::Local(local) => write!(f, "{} -> {}", self.name, local),
+            ExportKind::Expr => write!(f, "{}", self.name),
+            ExportKind::ReExport { source, name } => {
+                write!(f, "{} -> {} from \"{}\"", self.name, name, source)
+            }
+            ExportKind::ReExportAll { source } => {
+                write!(f, "* from \"{}\"", source)
+            }
+        }
+    }
+}
+
+/// Get all exports of a module in the order they are declared
+pub fn get_exports(module: &Module) -> Vec<Export> {
+    module
+        .body
+        .iter()
+        .filter_map(|item| match item {
+            ModuleItem::ModuleDecl(decl) => Some(decl),
+            _ => None,
+        })
+        .flat_map(get_decl_exports)
+        .collect()
+}
+
+fn local(span: Span, name: String, type_only: bool) -> Export {
+    Export { span, kind: ExportKind::Local(name.clone()), name, type_only }
+}
+
+fn get_decl_exports(decl: &ModuleDecl) -> Vec<Export> {
+    match decl {
+        ModuleDecl::ExportDecl(export) => {
+            let idents = match &export.decl {
+                Decl::Var(var) => var
+                    .decls
+                    .iter()
+                    .flat_map(|decl| utils::ast::get_pat_idents(&decl.name))
+                    .collect(),
+                Decl::Fn(decl) => vec![&decl.ident],
+                Decl::Class(decl) => vec![&decl.ident],
+                Decl::TsInterface(decl) => vec![&decl.id],
+                Decl::TsTypeAlias(decl) => vec![&decl.id],
+                Decl::TsEnum(decl) => vec![&decl.id],
+                Decl::TsModule(decl) => match &decl.id {
+                    TsModuleName::Ident(ident) => vec![ident],
+                    TsModuleName::Str(_) => vec![],
+                },
+                Decl::Using(_) => vec![],
+            };
+
+            let type_only = matches!(
+                export.decl,
+                Decl::TsInterface(_) | Decl::TsTypeAlias(_)
+            );
+
+            idents
+                .into_iter()
+                .map(|ident| {
+                    local(
+                        export.span,
+                        utils::ast::get_ident_name(ident),
+                        type_only,
+                    )
+                })
+                .collect()
+        }
+        ModuleDecl::ExportDefaultDecl(export) => {
+            let (ident, type_only) = match &export.decl {
+                DefaultDecl::Class(class) => (class.ident.as_ref(), false),
+                DefaultDecl::Fn(func) => (func.ident.as_ref(), false),
+                DefaultDecl::TsInterfaceDecl(decl) => (Some(&decl.id), true),
+            };
+
+            vec![Export {
+                span: export.span,
+                name: String::from("default"),
+                kind: ident.map_or(ExportKind::Expr, |ident| {
+                    ExportKind::Local(utils::ast::get_ident_name(ident))
+                }),
+                type_only,
+            }]
+        }
+        ModuleDecl::ExportDefaultExpr(export) => vec![Export {
+            span: export.span,
+            name: String::from("default"),
+            kind: get_expr_kind(&export.expr),
+            type_only: false,
+        }],
+        ModuleDecl::TsExportAssignment(export) => vec![Export {
+            span: export.span,
+            name: String::from("="),
+            kind: get_expr_kind(&export.expr),
+            type_only: false,
+        }],
+        ModuleDecl::TsImportEquals(decl) if decl.is_export => {
+            vec![local(
+                decl.span,
+                utils::ast::get_ident_name(&decl.id),
+                decl.is_type_only,
+            )]
+        }
+        ModuleDecl::ExportAll(export) => vec![Export {
+            span: export.span,
+            name: String::from("*"),
+            kind: ExportKind::ReExportAll {
+                source: export.src.value.to_string(),
+            },
+            type_only: export.type_only,
+        }],
+        ModuleDecl::ExportNamed(export) => {
+            let source = export.src.as_ref().map(|src| src.value.to_string());
+
+            export
+                .specifiers
+                .iter()
+                .map(|specifier| {
+                    let (orig, name, type_only) = match specifier {
+                        ExportSpecifier::Named(named) => {
+                            let orig = utils::ast::get_export_name(&named.orig);
+                            let name = named.exported.as_ref().map_or(
+                                orig.clone(),
+                                utils::ast::get_export_name,
+                            );
+
+                            (orig, name, named.is_type_only)
+                        }
+                        ExportSpecifier::Namespace(namespace) => (
+                            String::from("*"),
+                            utils::ast::get_export_name(&namespace.name),
+                            false,
+                        ),
+                        ExportSpecifier::Default(default) => (
+                            String::from("default"),
+                            utils::ast::get_ident_name(&default.exported),
+                            false,
+                        ),
+                    };
+
+                    let kind = match &source {
+                        Some(source) => ExportKind::ReExport {
+                            source: source.clone(),
+                            name: orig,
+                        },
+                        None => ExportKind::Local(orig),
+                    };
+
+                    Export {
+                        span: export.span,
+                        name,
+                        kind,
+                        type_only: export.type_only || type_only,
+                    }
+                })
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+fn get_expr_kind(expr: &Expr) -> ExportKind {
+    match expr {
+        Expr::Ident(ident) => {
+            ExportKind::Local(utils::ast::get_ident_name(ident))
+        }
+        _ => ExportKind::Expr,
+    }
+}
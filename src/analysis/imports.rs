@@ -1,12 +1,17 @@
+use serde::Deserialize;
 use std::path::PathBuf;
 use swc_common::Span;
-use swc_ecma_ast::ImportDecl;
+use swc_ecma_ast::{
+    CallExpr, Callee, ExportAll, ExportSpecifier, Expr, ImportDecl,
+    ImportSpecifier, Lit, Module, NamedExport, TsImportEqualsDecl, TsModuleRef,
+};
 use swc_ecma_visit::{Visit, VisitWith};
 
 use crate::parser::ParsedModule;
-use crate::processor::ProcessorRequest;
+use crate::processor::{Match, ProcessorRequest};
 use crate::utils;
 
+#[derive(Clone)]
 pub struct ImportsRequest {
     pub path: PathBuf,
     pub source: String,
@@ -18,11 +23,11 @@ impl ProcessorRequest for ImportsRequest {
         &self.path
     }
 
-    fn analyze(&self, parsed: &ParsedModule) -> Vec<Span> {
-        let mut visitor = ImportVisitor { imports: Vec::new(), request: &self };
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        let mut visitor = ImportVisitor { imports: Vec::new(), request: self };
 
         visitor.visit_module(&parsed.module);
-        visitor.imports
+        visitor.imports.into_iter().map(Match::from).collect()
     }
 }
 
@@ -33,24 +38,38 @@ struct ImportVisitor<'a> {
 
 impl<'a> Visit for ImportVisitor<'a> {
     fn visit_import_decl(&mut self, node: &ImportDecl) {
-        // First check that the import source matches the request
-        if node.src.value == self.request.source {
-            // If a specifier is provided, check that the import contains
-            // the given specifier.
-            let has_specifier = match &self.request.specifier {
-                Some(spec) => has_required_specifier(node, &spec),
-                None => true,
-            };
-
-            if has_specifier {
-                self.imports.push(node.span);
-            }
+        if is_import_match(
+            node,
+            &self.request.source,
+            self.request.specifier.as_ref(),
+        ) {
+            self.imports.push(node.span);
         }
 
         node.visit_children_with(self)
     }
 }
 
+/// Check if an import declaration has the given source and, if provided,
+/// contains the given specifier.
+pub fn is_import_match(
+    node: &ImportDecl,
+    source: &str,
+    specifier: Option<&String>,
+) -> bool {
+    // First check that the import source matches the request
+    if node.src.value != *source {
+        return false;
+    }
+
+    // If a specifier is provided, check that the import contains the given
+    // specifier.
+    match specifier {
+        Some(spec) => has_required_specifier(node, spec),
+        None => true,
+    }
+}
+
 fn has_required_specifier(import: &ImportDecl, name: &String) -> bool {
     let count = import
         .specifiers
@@ -61,3 +80,175 @@ fn has_required_specifier(import: &ImportDecl, name: &String) -> bool {
 
     count > 0
 }
+
+/// An import of another module. Besides import declarations, this includes
+/// re-exports, dynamic imports and `require` calls.
+#[derive(Clone)]
+pub struct ModuleImport {
+    pub span: Span,
+    pub source: String,
+    /// The names imported from the module. Namespace imports, dynamic imports
+    /// and `require` calls import every name which is represented as `*`.
+    pub names: Vec<String>,
+    pub kind: ImportKind,
+    pub type_only: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportKind {
+    Static,
+    ReExport,
+    Dynamic,
+    Require,
+}
+
+/// Get all imports of a module in the order they appear
+pub fn get_module_imports(module: &Module) -> Vec<ModuleImport> {
+    let mut visitor = ModuleImportVisitor { imports: Vec::new() };
+
+    visitor.visit_module(module);
+    visitor.imports
+}
+
+struct ModuleImportVisitor {
+    imports: Vec<ModuleImport>,
+}
+
+impl ModuleImportVisitor {
+    fn add(
+        &mut self,
+        span: Span,
+        source: String,
+        names: Vec<String>,
+        kind: ImportKind,
+        type_only: bool,
+    ) {
+        self.imports.push(ModuleImport {
+            span,
+            source,
+            names,
+            kind,
+            type_only,
+        });
+    }
+}
+
+impl Visit for ModuleImportVisitor {
+    fn visit_import_decl(&mut self, node: &ImportDecl) {
+        let names = node
+            .specifiers
+            .iter()
+            .map(|specifier| match specifier {
+                ImportSpecifier::Named(named) => {
+                    named.imported.as_ref().map_or_else(
+                        || utils::ast::get_ident_name(&named.local),
+                        utils::ast::get_export_name,
+                    )
+                }
+                ImportSpecifier::Default(_) => String::from("default"),
+                ImportSpecifier::Namespace(_) => String::from("*"),
+            })
+            .collect();
+
+        // An import is also type only when all of its specifiers are
+        let type_only = node.type_only
+            || (!node.specifiers.is_empty()
+                && node.specifiers.iter().all(|specifier| match specifier {
+                    ImportSpecifier::Named(named) => named.is_type_only,
+                    _ => false,
+                }));
+
+        self.add(
+            node.span,
+            node.src.value.to_string(),
+            names,
+            ImportKind::Static,
+            type_only,
+        );
+    }
+
+    fn visit_named_export(&mut self, node: &NamedExport) {
+        let src = match &node.src {
+            Some(src) => src,
+            None => return,
+        };
+
+        let names = node
+            .specifiers
+            .iter()
+            .map(|specifier| match specifier {
+                ExportSpecifier::Named(named) => {
+                    utils::ast::get_export_name(&named.orig)
+                }
+                ExportSpecifier::Namespace(_) => String::from("*"),
+                ExportSpecifier::Default(_) => String::from("default"),
+            })
+            .collect();
+
+        let type_only = node.type_only
+            || (!node.specifiers.is_empty()
+                && node.specifiers.iter().all(|specifier| match specifier {
+                    ExportSpecifier::Named(named) => named.is_type_only,
+                    _ => false,
+                }));
+
+        self.add(
+            node.span,
+            src.value.to_string(),
+            names,
+            ImportKind::ReExport,
+            type_only,
+        );
+    }
+
+    fn visit_export_all(&mut self, node: &ExportAll) {
+        self.add(
+            node.span,
+            node.src.value.to_string(),
+            vec![String::from("*")],
+            ImportKind::ReExport,
+            node.type_only,
+        );
+    }
+
+    fn visit_ts_import_equals_decl(&mut self, node: &TsImportEqualsDecl) {
+        if let TsModuleRef::TsExternalModuleRef(module) = &node.module_ref {
+            self.add(
+                node.span,
+                module.expr.value.to_string(),
+                vec![String::from("*")],
+                ImportKind::Require,
+                node.is_type_only,
+            );
+        }
+    }
+
+    fn visit_call_expr(&mut self, node: &CallExpr) {
+        let kind = match &node.callee {
+            Callee::Import(_) => Some(ImportKind::Dynamic),
+            Callee::Expr(expr) => match &**expr {
+                Expr::Ident(ident) if &*ident.sym == "require" => {
+                    Some(ImportKind::Require)
+                }
+                _ => None,
+            },
+            Callee::Super(_) => None,
+        };
+
+        // Only imports with a static source can be resolved
+        let source = node.args.first().and_then(|arg| match &*arg.expr {
+            Expr::Lit(Lit::Str(str)) => Some(str.value.to_string()),
+            Expr::Tpl(tpl) if tpl.exprs.is_empty() => {
+                tpl.quasis.first().map(|quasi| quasi.raw.to_string())
+            }
+            _ => None,
+        });
+
+        if let (Some(kind), Some(source)) = (kind, source) {
+            self.add(node.span, source, vec![String::from("*")], kind, false);
+        }
+
+        node.visit_children_with(self)
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use swc_ecma_ast::MemberExpr;
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::config::Severity;
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+use crate::utils;
+
+#[derive(Clone)]
+pub struct MembersRequest {
+    pub path: PathBuf,
+    pub pattern: String,
+    pub unique: bool,
+    /// Names already reported when only reporting unique names
+    seen: HashSet<String>,
+}
+
+impl MembersRequest {
+    pub fn new(path: PathBuf, pattern: String, unique: bool) -> MembersRequest {
+        MembersRequest { path, pattern, unique, seen: HashSet::new() }
+    }
+}
+
+impl ProcessorRequest for MembersRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        let mut visitor = MemberVisitor {
+            members: Vec::new(),
+            pattern: self.pattern.split('.').collect(),
+        };
+
+        visitor.visit_module(&parsed.module);
+
+        if !self.unique {
+            return visitor.members.into_iter().map(|(m, _)| m).collect();
+        }
+
+        // When only reporting unique names, the matched name is reported in
+        // place of the source line at its first occurrence.
+        visitor
+            .members
+            .into_iter()
+            .filter(|(_, name)| self.seen.insert(name.clone()))
+            .map(|(m, name)| Match {
+                span: m.span,
+                text: Some(name),
+                severity: Severity::Error,
+            })
+            .collect()
+    }
+}
+
+struct MemberVisitor<'a> {
+    members: Vec<(Match, String)>,
+    pattern: Vec<&'a str>,
+}
+
+impl<'a> MemberVisitor<'a> {
+    fn check(&mut self, node: &MemberExpr) {
+        if let Some(name) = utils::ast::get_member_path(node) {
+            if matches_pattern(&self.pattern, &name) {
+                self.members.push((Match::from(node.span), name));
+            }
+        }
+    }
+}
+
+impl<'a> Visit for MemberVisitor<'a> {
+    fn visit_member_expr(&mut self, node: &MemberExpr) {
+        self.check(node);
+        node.visit_children_with(self)
+    }
+}
+
+/// Check if a dotted member name matches the pattern. Each segment of the
+/// pattern must match the segment of the name exactly, except for `*` which
+/// matches any single segment.
+fn matches_pattern(pattern: &[&str], name: &str) -> bool {
+    let segments: Vec<&str> = name.split('.').collect();
+
+    segments.len() == pattern.len()
+        && pattern
+            .iter()
+            .zip(segments)
+            .all(|(expected, actual)| *expected == "*" || *expected == actual)
+}
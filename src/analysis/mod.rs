@@ -1,2 +1,16 @@
+pub mod calls;
+pub mod check;
+pub mod cycles;
+pub mod dependency_graph;
+pub mod deps;
+pub mod exports;
 pub mod imports;
+pub mod members;
+pub mod query;
+pub mod references;
+pub mod replace;
+pub mod rewrite_imports;
+pub mod rewrite_tags;
+pub mod search;
 pub mod tags;
+pub mod unused_exports;
@@ -0,0 +1,339 @@
+use std::path::{Path, PathBuf};
+
+use swc_common::{Loc, Span};
+
+use crate::edit::Edit;
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+
+/// A request combined with others in a query or batch. Requests are boxed
+/// since each operand can be a different command.
+pub trait Operand: ProcessorRequest {
+    fn clone_operand(&self) -> Box<dyn Operand>;
+}
+
+impl<R: ProcessorRequest + Clone + 'static> Operand for R {
+    fn clone_operand(&self) -> Box<dyn Operand> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Operand> {
+    fn clone(&self) -> Self {
+        // Boxes are operands themselves, so the box is dereferenced to clone
+        // the request inside it rather than boxing the box
+        (**self).clone_operand()
+    }
+}
+
+impl ProcessorRequest for Box<dyn Operand> {
+    fn path(&self) -> &PathBuf {
+        (**self).path()
+    }
+
+    fn analyze(&mut self, module: &ParsedModule) -> Vec<Match> {
+        (**self).analyze(module)
+    }
+
+    fn uses_all_modules(&self) -> bool {
+        (**self).uses_all_modules()
+    }
+
+    fn edit(&mut self, module: &ParsedModule) -> Vec<Edit> {
+        (**self).edit(module)
+    }
+
+    fn finish(&mut self) -> Vec<(String, Loc)> {
+        (**self).finish()
+    }
+
+    fn forget(&mut self, path: &Path) {
+        (**self).forget(path)
+    }
+
+    fn errors(&mut self) -> Vec<String> {
+        (**self).errors()
+    }
+}
+
+/// A boolean expression of operands, referenced by their index
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Operand(usize),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, matched: &[bool]) -> bool {
+        match self {
+            Expr::Operand(index) => matched[*index],
+            Expr::And(a, b) => a.eval(matched) && b.eval(matched),
+            Expr::Or(a, b) => a.eval(matched) || b.eval(matched),
+            Expr::Not(expr) => !expr.eval(matched),
+        }
+    }
+
+    /// Check if an operand can have matches in a module matching the
+    /// expression, which isn't the case when it is only used negated
+    pub fn is_positive(&self, index: usize) -> bool {
+        match self {
+            Expr::Operand(operand) => *operand == index,
+            Expr::And(a, b) | Expr::Or(a, b) => {
+                a.is_positive(index) || b.is_positive(index)
+            }
+            Expr::Not(_) => false,
+        }
+    }
+}
+
+/// An operand of a parsed query, which is either the arguments of a command
+/// or the name of a saved query
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    Command(Vec<String>),
+    Saved(String),
+}
+
+/// Parse a query such as `[imports react] and not ([tags Form] or saved)`.
+/// Commands are wrapped in brackets and split into arguments like a shell,
+/// while bare names refer to saved queries. `not` binds tighter than `and`,
+/// which binds tighter than `or`.
+pub fn parse(source: &str) -> Result<(Expr, Vec<Source>), String> {
+    let mut parser =
+        Parser { tokens: tokenize(source)?, pos: 0, sources: vec![] };
+    let expr = parser.parse_or()?;
+
+    match parser.tokens.get(parser.pos) {
+        Some(token) => Err(format!("Unexpected {}", token)),
+        None => Ok((expr, parser.sources)),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Command(Vec<String>),
+    Word(String),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Token::Command(args) => write!(f, "[{}]", args.join(" ")),
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '[' => {
+                let mut args = Vec::new();
+                let mut arg: Option<String> = None;
+                let mut quote = None;
+                let mut depth = 0;
+
+                loop {
+                    let c = match chars.next() {
+                        Some(c) => c,
+                        None if quote.is_some() => {
+                            return Err("Unterminated quote".to_string())
+                        }
+                        None => return Err("Unterminated [".to_string()),
+                    };
+
+                    match (c, quote) {
+                        (c, Some(q)) if c == q => quote = None,
+                        (c, Some(_)) => {
+                            arg.get_or_insert_with(String::new).push(c)
+                        }
+                        ('\'' | '"', None) => {
+                            quote = Some(c);
+                            arg.get_or_insert_with(String::new);
+                        }
+                        (']', None) if depth == 0 => break,
+                        (c, None) if c.is_whitespace() => {
+                            args.extend(arg.take());
+                        }
+                        (c, None) => {
+                            match c {
+                                '[' => depth += 1,
+                                ']' => depth -= 1,
+                                _ => {}
+                            }
+
+                            arg.get_or_insert_with(String::new).push(c);
+                        }
+                    }
+                }
+
+                args.extend(arg);
+
+                if args.is_empty() {
+                    return Err("Empty command []".to_string());
+                }
+
+                tokens.push(Token::Command(args));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+
+                while let Some(c) = chars.next_if(|c| {
+                    !c.is_whitespace() && !matches!(c, '(' | ')' | '[' | ']')
+                }) {
+                    word.push(c);
+                }
+
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    sources: Vec<Source>,
+}
+
+impl Parser {
+    fn eat(&mut self, keyword: &str) -> bool {
+        let is_match = matches!(
+            self.tokens.get(self.pos),
+            Some(Token::Word(word)) if word == keyword
+        );
+
+        if is_match {
+            self.pos += 1;
+        }
+
+        is_match
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+
+        while self.eat("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_not()?;
+
+        while self.eat("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        match self.eat("not") {
+            true => Ok(Expr::Not(Box::new(self.parse_not()?))),
+            false => self.parse_operand(),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr, String> {
+        let source = match self.tokens.get_mut(self.pos) {
+            Some(Token::Open) => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+
+                return match self.tokens.get(self.pos) {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(expr)
+                    }
+                    _ => Err("Expected )".to_string()),
+                };
+            }
+            Some(Token::Command(args)) => Source::Command(std::mem::take(args)),
+            Some(Token::Word(word))
+                if !matches!(word.as_str(), "and" | "or" | "not") =>
+            {
+                Source::Saved(word.clone())
+            }
+            Some(token) => return Err(format!("Unexpected {}", token)),
+            None => return Err("Expected a query".to_string()),
+        };
+
+        self.pos += 1;
+        self.sources.push(source);
+
+        Ok(Expr::Operand(self.sources.len() - 1))
+    }
+}
+
+/// Finds modules matching a boolean combination of other requests. Each
+/// matching module is reported once, or the matches of a selected operand
+/// are reported instead.
+#[derive(Clone)]
+pub struct QueryRequest {
+    pub path: PathBuf,
+    pub expr: Expr,
+    pub operands: Vec<Box<dyn Operand>>,
+    /// The index of the operand whose matches are reported
+    pub report: Option<usize>,
+}
+
+impl ProcessorRequest for QueryRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        let mut results: Vec<Vec<Match>> = self
+            .operands
+            .iter_mut()
+            .map(|operand| operand.analyze(parsed))
+            .collect();
+
+        let matched: Vec<bool> =
+            results.iter().map(|matches| !matches.is_empty()).collect();
+
+        if !self.expr.eval(&matched) {
+            return vec![];
+        }
+
+        if let Some(index) = self.report {
+            return std::mem::take(&mut results[index]);
+        }
+
+        // Modules are reported at their first match of an operand which isn't
+        // negated, or at the start of the file when there is none.
+        let first = results
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| self.expr.is_positive(*index))
+            .flat_map(|(_, matches)| matches)
+            .min_by_key(|m| m.span.lo);
+
+        let file = parsed.source_map.lookup_source_file(parsed.module.span.lo);
+
+        vec![first.unwrap_or_else(|| {
+            Match::from(Span::new(
+                file.start_pos,
+                file.start_pos,
+                Default::default(),
+            ))
+        })]
+    }
+}
@@ -0,0 +1,410 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use swc_common::Span;
+use swc_ecma_ast::{
+    ArrowExpr, AssignExpr, BlockStmt, BlockStmtOrExpr, CatchClause,
+    Constructor, Decl, Expr, FnExpr, ForHead, ForInStmt, ForOfStmt, ForStmt,
+    Function, Ident, ImportDecl, ImportSpecifier, JSXElementName, JSXObject,
+    ModuleDecl, ModuleExportName, ModuleItem, NamedExport, ParamOrTsParamProp,
+    PatOrExpr, Prop, SetterProp, Stmt, SwitchStmt, TsEntityName,
+    TsParamPropParam, VarDecl, VarDeclKind, VarDeclOrExpr,
+};
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+use crate::utils;
+
+#[derive(Clone)]
+pub struct ReferencesRequest {
+    pub path: PathBuf,
+    pub source: String,
+    pub specifier: String,
+}
+
+impl ProcessorRequest for ReferencesRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        let bindings = get_local_bindings(parsed, self);
+        if bindings.is_empty() {
+            return vec![];
+        }
+
+        let mut visitor = ReferenceVisitor {
+            references: Vec::new(),
+            bindings,
+            scopes: Vec::new(),
+        };
+
+        visitor.visit_module(&parsed.module);
+        visitor.references.into_iter().map(Match::from).collect()
+    }
+}
+
+/// Get the local names the requested specifier is bound to. The same
+/// specifier may be imported more than once under different aliases.
+fn get_local_bindings(
+    parsed: &ParsedModule,
+    request: &ReferencesRequest,
+) -> HashSet<String> {
+    parsed
+        .module
+        .body
+        .iter()
+        .filter_map(|item| match item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => Some(import),
+            _ => None,
+        })
+        .filter(|import| import.src.value == *request.source)
+        .flat_map(|import| import.specifiers.iter())
+        .filter_map(|specifier| match specifier {
+            ImportSpecifier::Named(named) => {
+                let imported = named.imported.as_ref().map_or_else(
+                    || utils::ast::get_ident_name(&named.local),
+                    utils::ast::get_export_name,
+                );
+
+                (imported == request.specifier).then_some(&named.local)
+            }
+            ImportSpecifier::Default(default) => {
+                (request.specifier == "default").then_some(&default.local)
+            }
+            ImportSpecifier::Namespace(namespace) => {
+                (request.specifier == "*").then_some(&namespace.local)
+            }
+        })
+        .map(utils::ast::get_ident_name)
+        .collect()
+}
+
+/// Finds references to the imported bindings while tracking the scopes which
+/// shadow them. Each scope on the stack contains the bindings it redeclares,
+/// so a reference is only reported when no enclosing scope shadows it.
+struct ReferenceVisitor {
+    references: Vec<Span>,
+    bindings: HashSet<String>,
+    scopes: Vec<HashSet<String>>,
+}
+
+impl ReferenceVisitor {
+    fn check(&mut self, ident: &Ident) {
+        let name = ident.sym.as_ref();
+
+        if self.bindings.contains(name)
+            && !self.scopes.iter().any(|scope| scope.contains(name))
+        {
+            self.references.push(ident.span);
+        }
+    }
+
+    /// Visit the children of a node inside a new scope declaring the given
+    /// names. Only names which shadow the imported bindings are tracked.
+    fn with_scope<'b, N, I>(&mut self, names: I, node: &N)
+    where
+        N: VisitWith<Self>,
+        I: IntoIterator<Item = &'b Ident>,
+    {
+        let scope = names
+            .into_iter()
+            .map(utils::ast::get_ident_name)
+            .filter(|name| self.bindings.contains(name))
+            .collect();
+
+        self.scopes.push(scope);
+        node.visit_children_with(self);
+        self.scopes.pop();
+    }
+}
+
+impl Visit for ReferenceVisitor {
+    fn visit_import_decl(&mut self, _: &ImportDecl) {
+        // The import itself is not a reference
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        if let Expr::Ident(ident) = node {
+            self.check(ident);
+        }
+
+        node.visit_children_with(self)
+    }
+
+    fn visit_assign_expr(&mut self, node: &AssignExpr) {
+        // Identifiers on the left side of an assignment are parsed as
+        // patterns, but they still reference the existing binding.
+        if let PatOrExpr::Pat(pat) = &node.left {
+            utils::ast::get_pat_idents(pat)
+                .into_iter()
+                .for_each(|ident| self.check(ident));
+        }
+
+        node.visit_children_with(self)
+    }
+
+    fn visit_prop(&mut self, node: &Prop) {
+        if let Prop::Shorthand(ident) = node {
+            self.check(ident);
+        }
+
+        node.visit_children_with(self)
+    }
+
+    fn visit_jsx_element_name(&mut self, node: &JSXElementName) {
+        if let JSXElementName::Ident(ident) = node {
+            self.check(ident);
+        }
+
+        node.visit_children_with(self)
+    }
+
+    fn visit_jsx_object(&mut self, node: &JSXObject) {
+        if let JSXObject::Ident(ident) = node {
+            self.check(ident);
+        }
+
+        node.visit_children_with(self)
+    }
+
+    fn visit_ts_entity_name(&mut self, node: &TsEntityName) {
+        if let TsEntityName::Ident(ident) = node {
+            self.check(ident);
+        }
+
+        node.visit_children_with(self)
+    }
+
+    fn visit_named_export(&mut self, node: &NamedExport) {
+        // Re-exports from another module don't reference local bindings
+        if node.src.is_some() {
+            return;
+        }
+
+        node.specifiers
+            .iter()
+            .filter_map(utils::ast::is_named_export_specifier)
+            .for_each(|specifier| {
+                if let ModuleExportName::Ident(ident) = &specifier.orig {
+                    self.check(ident);
+                }
+            });
+    }
+
+    fn visit_function(&mut self, node: &Function) {
+        let mut names: Vec<&Ident> = node
+            .params
+            .iter()
+            .flat_map(|param| utils::ast::get_pat_idents(&param.pat))
+            .collect();
+
+        if let Some(body) = &node.body {
+            names.extend(get_var_bindings(&body.stmts));
+        }
+
+        self.with_scope(names, node);
+    }
+
+    fn visit_fn_expr(&mut self, node: &FnExpr) {
+        // The name of a function expression is only bound inside of itself
+        self.with_scope(&node.ident, node);
+    }
+
+    fn visit_constructor(&mut self, node: &Constructor) {
+        let mut names: Vec<&Ident> = node
+            .params
+            .iter()
+            .flat_map(|param| match param {
+                ParamOrTsParamProp::Param(param) => {
+                    utils::ast::get_pat_idents(&param.pat)
+                }
+                ParamOrTsParamProp::TsParamProp(prop) => match &prop.param {
+                    TsParamPropParam::Ident(ident) => vec![&ident.id],
+                    TsParamPropParam::Assign(assign) => {
+                        utils::ast::get_pat_idents(&assign.left)
+                    }
+                },
+            })
+            .collect();
+
+        if let Some(body) = &node.body {
+            names.extend(get_var_bindings(&body.stmts));
+        }
+
+        self.with_scope(names, node);
+    }
+
+    fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
+        let mut names: Vec<&Ident> =
+            node.params.iter().flat_map(utils::ast::get_pat_idents).collect();
+
+        if let BlockStmtOrExpr::BlockStmt(body) = &*node.body {
+            names.extend(get_var_bindings(&body.stmts));
+        }
+
+        self.with_scope(names, node);
+    }
+
+    fn visit_setter_prop(&mut self, node: &SetterProp) {
+        let mut names = utils::ast::get_pat_idents(&node.param);
+
+        if let Some(body) = &node.body {
+            names.extend(get_var_bindings(&body.stmts));
+        }
+
+        self.with_scope(names, node);
+    }
+
+    fn visit_block_stmt(&mut self, node: &BlockStmt) {
+        self.with_scope(get_lexical_bindings(&node.stmts), node);
+    }
+
+    fn visit_switch_stmt(&mut self, node: &SwitchStmt) {
+        let names =
+            node.cases.iter().flat_map(|case| get_lexical_bindings(&case.cons));
+
+        self.with_scope(names, node);
+    }
+
+    fn visit_catch_clause(&mut self, node: &CatchClause) {
+        let names = node.param.iter().flat_map(utils::ast::get_pat_idents);
+
+        self.with_scope(names, node);
+    }
+
+    fn visit_for_stmt(&mut self, node: &ForStmt) {
+        let names = match &node.init {
+            Some(VarDeclOrExpr::VarDecl(decl)) => get_lexical_decl_idents(decl),
+            _ => vec![],
+        };
+
+        self.with_scope(names, node);
+    }
+
+    fn visit_for_in_stmt(&mut self, node: &ForInStmt) {
+        self.with_scope(get_for_head_bindings(&node.left), node);
+    }
+
+    fn visit_for_of_stmt(&mut self, node: &ForOfStmt) {
+        self.with_scope(get_for_head_bindings(&node.left), node);
+    }
+
+    fn visit_for_head(&mut self, node: &ForHead) {
+        // Loops without a declaration assign to an existing binding
+        if let ForHead::Pat(pat) = node {
+            utils::ast::get_pat_idents(pat)
+                .into_iter()
+                .for_each(|ident| self.check(ident));
+        }
+
+        node.visit_children_with(self)
+    }
+}
+
+fn get_var_decl_idents(decl: &VarDecl) -> Vec<&Ident> {
+    decl.decls
+        .iter()
+        .flat_map(|decl| utils::ast::get_pat_idents(&decl.name))
+        .collect()
+}
+
+/// Get the identifiers declared by a `let` or `const` declaration. `var`
+/// declarations are hoisted to the function scope so they are ignored here.
+fn get_lexical_decl_idents(decl: &VarDecl) -> Vec<&Ident> {
+    match decl.kind {
+        VarDeclKind::Var => vec![],
+        _ => get_var_decl_idents(decl),
+    }
+}
+
+fn get_for_head_bindings(head: &ForHead) -> Vec<&Ident> {
+    match head {
+        ForHead::VarDecl(decl) => get_lexical_decl_idents(decl),
+        _ => vec![],
+    }
+}
+
+/// Get the identifiers declared directly in a block. Function and class
+/// declarations are block scoped in modules since they are always strict.
+fn get_lexical_bindings(stmts: &[Stmt]) -> Vec<&Ident> {
+    stmts
+        .iter()
+        .flat_map(|stmt| match stmt {
+            Stmt::Decl(Decl::Var(decl)) => get_lexical_decl_idents(decl),
+            Stmt::Decl(Decl::Fn(decl)) => vec![&decl.ident],
+            Stmt::Decl(Decl::Class(decl)) => vec![&decl.ident],
+            Stmt::Decl(Decl::TsEnum(decl)) => vec![&decl.id],
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Get the `var` declarations hoisted to the top of a function body. Nested
+/// functions have their own var scope so they are not searched.
+fn get_var_bindings(stmts: &[Stmt]) -> Vec<&Ident> {
+    stmts.iter().flat_map(get_stmt_var_bindings).collect()
+}
+
+fn get_stmt_var_bindings(stmt: &Stmt) -> Vec<&Ident> {
+    match stmt {
+        Stmt::Decl(Decl::Var(decl)) if decl.kind == VarDeclKind::Var => {
+            get_var_decl_idents(decl)
+        }
+        Stmt::Block(block) => get_var_bindings(&block.stmts),
+        Stmt::If(stmt) => {
+            let mut idents = get_stmt_var_bindings(&stmt.cons);
+            if let Some(alt) = &stmt.alt {
+                idents.extend(get_stmt_var_bindings(alt));
+            }
+
+            idents
+        }
+        Stmt::For(stmt) => {
+            let mut idents = match &stmt.init {
+                Some(VarDeclOrExpr::VarDecl(decl))
+                    if decl.kind == VarDeclKind::Var =>
+                {
+                    get_var_decl_idents(decl)
+                }
+                _ => vec![],
+            };
+
+            idents.extend(get_stmt_var_bindings(&stmt.body));
+            idents
+        }
+        Stmt::ForIn(ForInStmt { left, body, .. })
+        | Stmt::ForOf(ForOfStmt { left, body, .. }) => {
+            let mut idents = match left {
+                ForHead::VarDecl(decl) if decl.kind == VarDeclKind::Var => {
+                    get_var_decl_idents(decl)
+                }
+                _ => vec![],
+            };
+
+            idents.extend(get_stmt_var_bindings(body));
+            idents
+        }
+        Stmt::While(stmt) => get_stmt_var_bindings(&stmt.body),
+        Stmt::DoWhile(stmt) => get_stmt_var_bindings(&stmt.body),
+        Stmt::Labeled(stmt) => get_stmt_var_bindings(&stmt.body),
+        Stmt::Try(stmt) => {
+            let mut idents = get_var_bindings(&stmt.block.stmts);
+            if let Some(handler) = &stmt.handler {
+                idents.extend(get_var_bindings(&handler.body.stmts));
+            }
+            if let Some(finalizer) = &stmt.finalizer {
+                idents.extend(get_var_bindings(&finalizer.stmts));
+            }
+
+            idents
+        }
+        Stmt::Switch(stmt) => stmt
+            .cases
+            .iter()
+            .flat_map(|case| get_var_bindings(&case.cons))
+            .collect(),
+        _ => vec![],
+    }
+}
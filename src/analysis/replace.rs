@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use swc_common::SourceFile;
+
+use crate::edit::{self, Edit};
+use crate::parser::ParsedModule;
+use crate::pattern::{Pattern, PatternMatch, Template};
+use crate::processor::{Match, ProcessorRequest};
+
+#[derive(Clone)]
+pub struct ReplaceRequest {
+    pub path: PathBuf,
+    pub pattern: Pattern,
+    pub template: Template,
+}
+
+impl ReplaceRequest {
+    /// Get the edit replacing a match and the index of the next match which
+    /// isn't nested in it. Nested matches are replaced first, so the code
+    /// bound to metavariables is pasted with their replacements.
+    fn replace(
+        &self,
+        file: &SourceFile,
+        matches: &[PatternMatch],
+        index: usize,
+    ) -> (Edit, usize) {
+        let m = &matches[index];
+        let mut nested = Vec::new();
+        let mut next = index + 1;
+
+        while matches.get(next).is_some_and(|n| n.span.lo < m.span.hi) {
+            let (edit, after) = self.replace(file, matches, next);
+            nested.push(edit);
+            next = after;
+        }
+
+        let text = self
+            .template
+            .render(m, &|span| edit::apply_to_span(file, span, &nested));
+
+        (Edit::replace(m.span, text), next)
+    }
+}
+
+impl ProcessorRequest for ReplaceRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, _parsed: &ParsedModule) -> Vec<Match> {
+        vec![]
+    }
+
+    fn edit(&mut self, parsed: &ParsedModule) -> Vec<Edit> {
+        let file = parsed.source_map.lookup_source_file(parsed.module.span.lo);
+
+        // Matches are sorted with outer matches first, so each match is
+        // followed by the matches nested in it
+        let matches = self.pattern.find(&parsed.module);
+        let mut edits = Vec::new();
+        let mut index = 0;
+
+        while index < matches.len() {
+            let (edit, next) = self.replace(&file, &matches, index);
+            edits.push(edit);
+            index = next;
+        }
+
+        edits
+    }
+}
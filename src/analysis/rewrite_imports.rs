@@ -0,0 +1,487 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use swc_common::{BytePos, SourceFile, Span};
+use swc_ecma_ast::{
+    CallExpr, Callee, ExportAll, Expr, ImportDecl, ImportSpecifier, Lit,
+    ModuleDecl, ModuleExportName, ModuleItem, NamedExport, Str,
+    TsImportEqualsDecl, TsModuleRef,
+};
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::analysis::imports;
+use crate::edit::{self, Edit};
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+use crate::utils;
+
+#[derive(Clone)]
+pub struct RewriteImportsRequest {
+    pub path: PathBuf,
+    /// The import source to rewrite (e.g., @acme/old-ui)
+    pub from: String,
+    /// The new import source (e.g., @acme/ui)
+    pub to: String,
+    /// The imported names to move to the new source, splitting them from
+    /// their import declarations. Every import is moved if empty.
+    pub specifiers: Vec<String>,
+    /// New imported names keyed by the current name
+    pub renames: HashMap<String, String>,
+}
+
+/// A specifier of a generated import declaration
+#[derive(PartialEq)]
+enum Part {
+    Default(String),
+    Namespace(String),
+    Named(String),
+}
+
+/// The formatting of an existing import declaration, used to format the
+/// declarations generated from it.
+struct Style {
+    quote: char,
+    semicolon: bool,
+    type_only: bool,
+}
+
+impl RewriteImportsRequest {
+    /// Check if a specifier should be moved to the new source
+    fn is_selected(&self, specifier: &ImportSpecifier) -> bool {
+        self.specifiers.is_empty()
+            || self
+                .specifiers
+                .contains(&utils::ast::get_imported_name(specifier))
+    }
+
+    /// Convert a specifier to a part of a generated declaration, renaming it
+    /// when requested
+    fn to_part(&self, specifier: &ImportSpecifier, rename: bool) -> Part {
+        match specifier {
+            ImportSpecifier::Default(default) => {
+                Part::Default(utils::ast::get_ident_name(&default.local))
+            }
+            ImportSpecifier::Namespace(namespace) => {
+                Part::Namespace(utils::ast::get_ident_name(&namespace.local))
+            }
+            ImportSpecifier::Named(named) => {
+                let local = utils::ast::get_ident_name(&named.local);
+                let imported = utils::ast::get_imported_name(specifier);
+                let imported = match self.renames.get(&imported) {
+                    Some(renamed) if rename => renamed.clone(),
+                    _ => match &named.imported {
+                        Some(ModuleExportName::Str(str)) => {
+                            format!("\"{}\"", str.value)
+                        }
+                        _ => imported,
+                    },
+                };
+
+                let prefix = if named.is_type_only { "type " } else { "" };
+
+                if imported == local {
+                    Part::Named(format!("{}{}", prefix, local))
+                } else {
+                    Part::Named(format!("{}{} as {}", prefix, imported, local))
+                }
+            }
+        }
+    }
+
+    /// Get the edits renaming the selected specifiers of a declaration in
+    /// place
+    fn rename_edits(&self, decl: &ImportDecl) -> Vec<Edit> {
+        decl.specifiers
+            .iter()
+            .filter(|specifier| self.is_selected(specifier))
+            .filter_map(|specifier| match specifier {
+                ImportSpecifier::Named(named)
+                    if self.renames.contains_key(
+                        &utils::ast::get_imported_name(specifier),
+                    ) =>
+                {
+                    match self.to_part(specifier, true) {
+                        Part::Named(text) => {
+                            Some(Edit::replace(named.span, text))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn source_edit(&self, file: &SourceFile, str: &Str) -> Edit {
+        let quote = edit::source_text(file, str.span).chars().next();
+        let quote = quote.unwrap_or('\'');
+
+        Edit::replace(str.span, format!("{}{}{}", quote, self.to, quote))
+    }
+}
+
+impl ProcessorRequest for RewriteImportsRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, _parsed: &ParsedModule) -> Vec<Match> {
+        vec![]
+    }
+
+    fn edit(&mut self, parsed: &ParsedModule) -> Vec<Edit> {
+        let file = parsed.source_map.lookup_source_file(parsed.module.span.lo);
+        let decls: Vec<&ImportDecl> = parsed
+            .module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) => Some(decl),
+                _ => None,
+            })
+            .collect();
+
+        let is_moving = self.from != self.to;
+
+        // Moved specifiers are merged into an existing import of the new
+        // source when possible
+        let target = decls.iter().find(|decl| {
+            is_moving
+                && imports::is_import_match(decl, &self.to, None)
+                && !decl.type_only
+                && !decl
+                    .specifiers
+                    .iter()
+                    .any(|s| matches!(s, ImportSpecifier::Namespace(_)))
+        });
+
+        let mut edits = Vec::new();
+        let mut moved: Vec<Part> = Vec::new();
+        let mut split: Option<&ImportDecl> = None;
+
+        for decl in decls
+            .iter()
+            .filter(|decl| imports::is_import_match(decl, &self.from, None))
+        {
+            // Side effect imports can only be moved as a whole
+            if decl.specifiers.is_empty() {
+                if is_moving && self.specifiers.is_empty() {
+                    edits.push(self.source_edit(&file, &decl.src));
+                }
+
+                continue;
+            }
+
+            let (selected, kept): (Vec<_>, Vec<_>) =
+                decl.specifiers.iter().partition(|s| self.is_selected(s));
+
+            if selected.is_empty() {
+                continue;
+            }
+
+            // Declarations moved as a whole are edited in place to preserve
+            // their formatting
+            if !is_moving
+                || (kept.is_empty() && (target.is_none() || decl.type_only))
+            {
+                if is_moving {
+                    edits.push(self.source_edit(&file, &decl.src));
+                }
+
+                edits.extend(self.rename_edits(decl));
+                continue;
+            }
+
+            moved.extend(selected.iter().map(|specifier| {
+                match self.to_part(specifier, true) {
+                    Part::Named(text) if decl.type_only => {
+                        Part::Named(format!("type {}", text))
+                    }
+                    part => part,
+                }
+            }));
+
+            if kept.is_empty() {
+                edits.push(Edit::delete(edit::line_span(&file, decl.span)));
+            } else {
+                let parts: Vec<Part> =
+                    kept.iter().map(|s| self.to_part(s, false)).collect();
+                let style = get_style(&file, decl);
+
+                edits.push(Edit::replace(
+                    decl.span,
+                    format_imports(&parts, &self.from, &style),
+                ));
+                split.get_or_insert(decl);
+            }
+        }
+
+        if !moved.is_empty() {
+            match (target, split) {
+                (Some(target), _) => {
+                    let mut parts: Vec<Part> = target
+                        .specifiers
+                        .iter()
+                        .map(|s| self.to_part(s, false))
+                        .collect();
+                    let mut added: Vec<Part> = Vec::new();
+
+                    for part in moved {
+                        if !parts.contains(&part) && !added.contains(&part) {
+                            added.push(part);
+                        }
+                    }
+
+                    // The target is only formatted again when the specifiers
+                    // can't be inserted into it
+                    match merge_edits(&file, target, &added) {
+                        Some(merged) => edits.extend(merged),
+                        None => {
+                            parts.extend(added);
+                            edits.push(Edit::replace(
+                                target.span,
+                                format_imports(
+                                    &parts,
+                                    &self.to,
+                                    &get_style(&file, target),
+                                ),
+                            ));
+                        }
+                    }
+                }
+                (None, Some(split)) => {
+                    let style =
+                        Style { type_only: false, ..get_style(&file, split) };
+
+                    edits.push(Edit::insert(
+                        split.span.hi,
+                        format!(
+                            "\n{}",
+                            format_imports(&moved, &self.to, &style)
+                        ),
+                    ));
+                }
+                (None, None) => {}
+            }
+        }
+
+        // Re-exports, dynamic imports and `require` calls don't have
+        // specifiers which can be moved, so they are only rewritten when
+        // moving every import.
+        if is_moving && self.specifiers.is_empty() {
+            let mut visitor =
+                SourceVisitor { source: &self.from, sources: Vec::new() };
+
+            visitor.visit_module(&parsed.module);
+            edits.extend(
+                visitor.sources.iter().map(|str| self.source_edit(&file, str)),
+            );
+        }
+
+        edits
+    }
+}
+
+/// Finds the sources of imports other than import declarations
+struct SourceVisitor<'a> {
+    source: &'a str,
+    sources: Vec<Str>,
+}
+
+impl SourceVisitor<'_> {
+    fn add(&mut self, str: &Str) {
+        if str.value == *self.source {
+            self.sources.push(str.clone());
+        }
+    }
+}
+
+impl Visit for SourceVisitor<'_> {
+    fn visit_named_export(&mut self, node: &NamedExport) {
+        if let Some(src) = &node.src {
+            self.add(src);
+        }
+    }
+
+    fn visit_export_all(&mut self, node: &ExportAll) {
+        self.add(&node.src);
+    }
+
+    fn visit_ts_import_equals_decl(&mut self, node: &TsImportEqualsDecl) {
+        if let TsModuleRef::TsExternalModuleRef(module) = &node.module_ref {
+            self.add(&module.expr);
+        }
+    }
+
+    fn visit_call_expr(&mut self, node: &CallExpr) {
+        let is_import = match &node.callee {
+            Callee::Import(_) => true,
+            Callee::Expr(expr) => {
+                matches!(&**expr, Expr::Ident(ident) if &*ident.sym == "require")
+            }
+            Callee::Super(_) => false,
+        };
+
+        if is_import {
+            if let Some(Expr::Lit(Lit::Str(str))) =
+                node.args.first().map(|arg| &*arg.expr)
+            {
+                self.add(str);
+            }
+        }
+
+        node.visit_children_with(self)
+    }
+}
+
+/// Get the edits inserting specifiers into an existing import declaration,
+/// preserving its formatting. Named imports are added after the last named
+/// import, on their own lines when the declaration spans several lines.
+/// Returns `None` when the specifiers can't be added to the declaration, such
+/// as a second default import.
+fn merge_edits(
+    file: &SourceFile,
+    decl: &ImportDecl,
+    parts: &[Part],
+) -> Option<Vec<Edit>> {
+    let default =
+        decl.specifiers.iter().find_map(|specifier| match specifier {
+            ImportSpecifier::Default(default) => Some(default.span),
+            _ => None,
+        });
+    let named: Vec<Span> = decl
+        .specifiers
+        .iter()
+        .filter_map(|specifier| match specifier {
+            ImportSpecifier::Named(named) => Some(named.span),
+            _ => None,
+        })
+        .collect();
+
+    // The text from the start of the declaration to its first named import
+    let braces = named.first().and_then(|first| {
+        let before =
+            edit::source_text(file, Span { hi: first.lo, ..decl.span });
+        Some((before, before.rfind('{')?))
+    });
+
+    let mut edits = Vec::new();
+    let mut names = Vec::new();
+
+    for part in parts {
+        match part {
+            Part::Default(name) if default.is_none() => {
+                let (_, brace) = braces?;
+                edits.push(Edit::insert(
+                    decl.span.lo + BytePos(brace as u32),
+                    format!("{}, ", name),
+                ));
+            }
+            Part::Named(text) => names.push(text.as_str()),
+            _ => return None,
+        }
+    }
+
+    if names.is_empty() {
+        return Some(edits);
+    }
+
+    match (named.last(), braces, default) {
+        (Some(last), Some((before, brace)), _) => {
+            let gap = &before[brace + 1..];
+            let separator = match gap.rfind('\n') {
+                Some(newline) => format!(",\n{}", &gap[newline + 1..]),
+                None => String::from(", "),
+            };
+
+            edits.push(Edit::insert(
+                last.hi,
+                names
+                    .iter()
+                    .map(|name| format!("{}{}", separator, name))
+                    .collect::<String>(),
+            ));
+        }
+        (None, _, Some(default)) => edits.push(Edit::insert(
+            default.hi,
+            format!(", {{ {} }}", names.join(", ")),
+        )),
+        _ => return None,
+    }
+
+    Some(edits)
+}
+
+fn get_style(file: &SourceFile, decl: &ImportDecl) -> Style {
+    Style {
+        quote: edit::source_text(file, decl.src.span)
+            .chars()
+            .next()
+            .unwrap_or('\''),
+        semicolon: edit::source_text(file, decl.span).ends_with(';'),
+        type_only: decl.type_only,
+    }
+}
+
+/// Format import declarations containing the given specifiers. A declaration
+/// can have a single default import and either a namespace import or named
+/// imports, so multiple declarations are generated when needed.
+fn format_imports(parts: &[Part], source: &str, style: &Style) -> String {
+    #[derive(Default)]
+    struct Clause<'a> {
+        default: Option<&'a str>,
+        namespace: Option<&'a str>,
+        named: Vec<&'a str>,
+    }
+
+    let mut clauses: Vec<Clause> = Vec::new();
+
+    for part in parts {
+        let index = clauses.iter().position(|clause| match part {
+            Part::Default(_) => clause.default.is_none(),
+            Part::Namespace(_) => {
+                clause.namespace.is_none() && clause.named.is_empty()
+            }
+            Part::Named(_) => clause.namespace.is_none(),
+        });
+
+        let clause = match index {
+            Some(index) => &mut clauses[index],
+            None => {
+                clauses.push(Clause::default());
+                clauses.last_mut().unwrap()
+            }
+        };
+
+        match part {
+            Part::Default(name) => clause.default = Some(name),
+            Part::Namespace(name) => clause.namespace = Some(name),
+            Part::Named(text) => clause.named.push(text),
+        }
+    }
+
+    clauses
+        .iter()
+        .map(|clause| {
+            let mut items = Vec::new();
+
+            items.extend(clause.default.map(String::from));
+            items.extend(clause.namespace.map(|name| format!("* as {}", name)));
+
+            if !clause.named.is_empty() {
+                items.push(format!("{{ {} }}", clause.named.join(", ")));
+            }
+
+            format!(
+                "import {}{} from {}{}{}{}",
+                if style.type_only { "type " } else { "" },
+                items.join(", "),
+                style.quote,
+                source,
+                style.quote,
+                if style.semicolon { ";" } else { "" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
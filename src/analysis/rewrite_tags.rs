@@ -0,0 +1,361 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use swc_common::{BytePos, SourceFile, Span, Spanned};
+use swc_ecma_ast::{
+    ImportDecl, ImportSpecifier, JSXAttr, JSXAttrValue, JSXElement, ModuleDecl,
+    ModuleItem,
+};
+use swc_ecma_visit::Visit;
+
+use crate::analysis::references::ReferencesRequest;
+use crate::analysis::tags::{self, ElementVisitor};
+use crate::edit::{self, Edit};
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+use crate::utils;
+
+#[derive(Clone)]
+pub struct RewriteTagsRequest {
+    pub path: PathBuf,
+    pub name: String,
+    pub attribute: Option<String>,
+    pub value: Option<String>,
+    /// The new name of the tag
+    pub rename: Option<String>,
+    /// New attribute names keyed by the current name
+    pub rename_attributes: HashMap<String, String>,
+    /// Attribute values to set, adding the attribute when missing. Attributes
+    /// without a value are boolean attributes.
+    pub set: Vec<(String, Option<String>)>,
+    /// Attributes to remove
+    pub remove: Vec<String>,
+}
+
+impl RewriteTagsRequest {
+    fn element_edits(
+        &self,
+        file: &SourceFile,
+        node: &JSXElement,
+        edits: &mut Vec<Edit>,
+    ) {
+        let opening = &node.opening;
+
+        if let Some(rename) = &self.rename {
+            edits.push(Edit::replace(opening.name.span(), rename));
+
+            if let Some(closing) = &node.closing {
+                edits.push(Edit::replace(closing.name.span(), rename));
+            }
+        }
+
+        // Attributes are set and removed by their name after renaming
+        let mut names = HashSet::new();
+
+        for attr in
+            opening.attrs.iter().filter_map(utils::ast::is_jsx_attribute)
+        {
+            let name = tags::get_attribute_name(attr);
+            let renamed = self.rename_attributes.get(&name);
+            let name = renamed.unwrap_or(&name);
+
+            if self.remove.contains(name) {
+                edits.push(Edit::delete(with_leading_whitespace(
+                    file, attr.span,
+                )));
+                continue;
+            }
+
+            names.insert(name.clone());
+
+            match self.set.iter().find(|(set, _)| set == name) {
+                Some((_, value)) => edits.push(Edit::replace(
+                    attr.span,
+                    format_attribute(
+                        name,
+                        value.as_deref(),
+                        get_quote(file, attr),
+                    ),
+                )),
+                None if renamed.is_some() => {
+                    edits.push(Edit::replace(attr.name.span(), name))
+                }
+                None => {}
+            }
+        }
+
+        // New attributes are added after the existing attributes, before the
+        // end of the tag since spread attributes don't span their braces
+        let text = edit::source_text(file, opening.span);
+        let end = match opening.self_closing {
+            true => text.trim_end_matches('>').trim_end_matches('/'),
+            false => text.trim_end_matches('>'),
+        };
+        let pos = opening.span.lo + BytePos(end.trim_end().len() as u32);
+
+        for (name, value) in &self.set {
+            if !names.contains(name) && !self.remove.contains(name) {
+                edits.push(Edit::insert(
+                    pos,
+                    format!(
+                        " {}",
+                        format_attribute(name, value.as_deref(), '"')
+                    ),
+                ));
+            }
+        }
+    }
+
+    /// Get the edits updating the import of a renamed tag. The old import is
+    /// kept when the binding is still referenced by tags which weren't
+    /// renamed or other code.
+    fn import_edits(
+        &self,
+        parsed: &ParsedModule,
+        file: &SourceFile,
+        rename: &str,
+        renamed: &[Span],
+    ) -> Vec<Edit> {
+        let decls: Vec<&ImportDecl> = parsed
+            .module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) => Some(decl),
+                _ => None,
+            })
+            .collect();
+
+        let is_imported = decls
+            .iter()
+            .flat_map(|decl| decl.specifiers.iter())
+            .any(|specifier| get_local_name(specifier) == rename);
+
+        let mut edits = Vec::new();
+
+        for decl in &decls {
+            for (index, specifier) in decl.specifiers.iter().enumerate() {
+                if get_local_name(specifier) != self.name {
+                    continue;
+                }
+
+                let mut references = ReferencesRequest {
+                    path: self.path.clone(),
+                    source: decl.src.value.to_string(),
+                    specifier: utils::ast::get_imported_name(specifier),
+                };
+
+                let is_used = references.analyze(parsed).iter().any(|m| {
+                    !renamed.contains(&m.span)
+                        && edit::source_text(file, m.span) == self.name
+                });
+
+                match (specifier, is_used, is_imported) {
+                    (_, true, true) => {}
+                    (_, false, true) => {
+                        edits.push(delete_specifier(file, decl, index));
+                    }
+                    // Aliased imports only rename the alias, otherwise the
+                    // import is renamed along with the tag.
+                    (ImportSpecifier::Named(named), false, false) => {
+                        edits.push(Edit::replace(named.local.span, rename));
+                    }
+                    (ImportSpecifier::Named(named), true, false) => {
+                        let text = match &named.imported {
+                            Some(imported) => format!(
+                                "{} as {}",
+                                edit::source_text(file, imported.span()),
+                                rename
+                            ),
+                            None => rename.to_string(),
+                        };
+
+                        edits.push(Edit::insert(
+                            named.span.hi,
+                            format!(", {}", text),
+                        ));
+                    }
+                    (ImportSpecifier::Default(default), false, false) => {
+                        edits.push(Edit::replace(default.local.span, rename));
+                    }
+                    (ImportSpecifier::Default(_), true, false) => {
+                        let semicolon = match edit::source_text(file, decl.span)
+                            .ends_with(';')
+                        {
+                            true => ";",
+                            false => "",
+                        };
+
+                        edits.push(Edit::insert(
+                            decl.span.hi,
+                            format!(
+                                "\nimport {} from {}{}",
+                                rename,
+                                edit::source_text(file, decl.src.span),
+                                semicolon
+                            ),
+                        ));
+                    }
+                    // Namespace imports are referenced by member tags, which
+                    // don't have a single binding to rename
+                    (ImportSpecifier::Namespace(_), _, _) => {}
+                }
+            }
+        }
+
+        edits
+    }
+}
+
+impl ProcessorRequest for RewriteTagsRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, _parsed: &ParsedModule) -> Vec<Match> {
+        vec![]
+    }
+
+    fn edit(&mut self, parsed: &ParsedModule) -> Vec<Edit> {
+        let file = parsed.source_map.lookup_source_file(parsed.module.span.lo);
+        let mut edits = Vec::new();
+        let mut renamed = Vec::new();
+
+        let mut visitor = ElementVisitor {
+            name: &self.name,
+            attribute: self.attribute.as_deref(),
+            value: self.value.as_deref(),
+            on_element: |node: &JSXElement| {
+                renamed.push(node.opening.name.span());
+                renamed.extend(node.closing.iter().map(|c| c.name.span()));
+
+                self.element_edits(&file, node, &mut edits);
+            },
+        };
+
+        visitor.visit_module(&parsed.module);
+
+        // Only tags referencing a single binding have an import to update
+        match &self.rename {
+            Some(rename)
+                if !renamed.is_empty()
+                    && *rename != self.name
+                    && is_identifier(&self.name)
+                    && is_identifier(rename) =>
+            {
+                edits
+                    .extend(self.import_edits(parsed, &file, rename, &renamed));
+            }
+            _ => {}
+        }
+
+        edits
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.contains(['.', ':'])
+}
+
+fn get_local_name(specifier: &ImportSpecifier) -> String {
+    match specifier {
+        ImportSpecifier::Named(named) => {
+            utils::ast::get_ident_name(&named.local)
+        }
+        ImportSpecifier::Default(default) => {
+            utils::ast::get_ident_name(&default.local)
+        }
+        ImportSpecifier::Namespace(namespace) => {
+            utils::ast::get_ident_name(&namespace.local)
+        }
+    }
+}
+
+/// Get the quote used by a string attribute value, defaulting to double
+/// quotes for new values
+fn get_quote(file: &SourceFile, attr: &JSXAttr) -> char {
+    match &attr.value {
+        Some(JSXAttrValue::Lit(lit)) => {
+            edit::source_text(file, lit.span()).chars().next().unwrap_or('"')
+        }
+        _ => '"',
+    }
+}
+
+/// Format an attribute. Values wrapped in braces are expressions, while other
+/// values are strings. Attributes without a value are boolean attributes.
+fn format_attribute(name: &str, value: Option<&str>, quote: char) -> String {
+    match value {
+        None => name.to_string(),
+        Some(value) if value.starts_with('{') => format!("{}={}", name, value),
+        Some(value) => {
+            let quote = match value.contains(quote) {
+                true if quote == '"' => '\'',
+                true => '"',
+                false => quote,
+            };
+
+            format!("{}={}{}{}", name, quote, value, quote)
+        }
+    }
+}
+
+/// Extend the span of an attribute to include the whitespace before it, so
+/// that deleting it doesn't leave extra whitespace
+fn with_leading_whitespace(file: &SourceFile, span: Span) -> Span {
+    let lo = (span.lo - file.start_pos).0 as usize;
+    let whitespace = file.src[..lo].len()
+        - file.src[..lo].trim_end_matches(char::is_whitespace).len();
+
+    Span { lo: span.lo - BytePos(whitespace as u32), ..span }
+}
+
+/// Delete a specifier from an import declaration along with its separator,
+/// or the whole declaration when it is the only specifier
+fn delete_specifier(
+    file: &SourceFile,
+    decl: &ImportDecl,
+    index: usize,
+) -> Edit {
+    let specifiers = &decl.specifiers;
+    let specifier = &specifiers[index];
+    let span = specifier.span();
+
+    if specifiers.len() == 1 {
+        return Edit::delete(edit::line_span(file, decl.span));
+    }
+
+    if let Some(next) = specifiers.get(index + 1) {
+        // Deleting a default import keeps the braces of the named imports
+        let hi = match next {
+            ImportSpecifier::Named(_)
+                if matches!(specifier, ImportSpecifier::Default(_)) =>
+            {
+                let hi = (span.hi - file.start_pos).0 as usize;
+                let brace = file.src[hi..].find('{').unwrap_or(0);
+
+                span.hi + BytePos(brace as u32)
+            }
+            _ => next.span().lo,
+        };
+
+        return Edit::delete(Span { hi, ..span });
+    }
+
+    let prev = &specifiers[index - 1];
+    let is_only_named = !matches!(prev, ImportSpecifier::Named(_))
+        && matches!(specifier, ImportSpecifier::Named(_));
+
+    // Deleting the only named import also deletes its braces
+    let hi = match is_only_named {
+        true => {
+            let hi = (span.hi - file.start_pos).0 as usize;
+            let brace = file.src[hi..].find('}').map_or(0, |index| index + 1);
+
+            span.hi + BytePos(brace as u32)
+        }
+        false => span.hi,
+    };
+
+    Edit::delete(Span { lo: prev.span().hi, hi, ..span })
+}
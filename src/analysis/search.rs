@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use crate::config::Severity;
+use crate::parser::ParsedModule;
+use crate::pattern::Pattern;
+use crate::processor::{Match, ProcessorRequest};
+
+#[derive(Clone)]
+pub struct SearchRequest {
+    pub path: PathBuf,
+    pub pattern: Pattern,
+}
+
+impl ProcessorRequest for SearchRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        let source = &parsed.source_map;
+        let file = source.lookup_source_file(parsed.module.span.lo);
+
+        self.pattern
+            .find(&parsed.module)
+            .into_iter()
+            .map(|m| {
+                if m.bindings.is_empty() {
+                    return Match::from(m.span);
+                }
+
+                // Bindings are logged after the line containing the match,
+                // with whitespace collapsed so they fit on a single line.
+                let loc = source.lookup_char_pos(m.span.lo);
+                let line = file.get_line(loc.line - 1).unwrap_or_default();
+                let bindings: Vec<String> = m
+                    .bindings
+                    .iter()
+                    .map(|(name, binding)| {
+                        let text = binding.text(&file);
+                        let text: Vec<&str> = text.split_whitespace().collect();
+
+                        format!("${}={}", name, text.join(" "))
+                    })
+                    .collect();
+
+                Match {
+                    span: m.span,
+                    text: Some(format!(
+                        "{} [{}]",
+                        line.trim(),
+                        bindings.join(", ")
+                    )),
+                    severity: Severity::Error,
+                }
+            })
+            .collect()
+    }
+}
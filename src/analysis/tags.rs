@@ -1,15 +1,15 @@
 use std::path::PathBuf;
-use swc_common::Span;
 use swc_ecma_ast::{
     Expr, JSXAttr, JSXAttrName, JSXAttrValue, JSXElement, JSXElementName,
-    JSXExpr, JSXMemberExpr, JSXObject, Lit,
+    JSXExpr, JSXMemberExpr, JSXObject,
 };
 use swc_ecma_visit::{Visit, VisitWith};
 
 use crate::parser::ParsedModule;
-use crate::processor::ProcessorRequest;
+use crate::processor::{Match, ProcessorRequest};
 use crate::utils;
 
+#[derive(Clone)]
 pub struct TagsRequest {
     pub path: PathBuf,
     pub name: String,
@@ -22,35 +22,42 @@ impl ProcessorRequest for TagsRequest {
         &self.path
     }
 
-    fn analyze(&self, parsed: &ParsedModule) -> Vec<Span> {
-        let mut visitor =
-            ElementVisitor { elements: Vec::new(), request: &self };
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        let mut elements = Vec::new();
+        let mut visitor = ElementVisitor {
+            name: &self.name,
+            attribute: self.attribute.as_deref(),
+            value: self.value.as_deref(),
+            on_element: |node: &JSXElement| elements.push(node.span),
+        };
 
         visitor.visit_module(&parsed.module);
-        visitor.elements
+        elements.into_iter().map(Match::from).collect()
     }
 }
 
-struct ElementVisitor<'a> {
-    elements: Vec<Span>,
-    request: &'a TagsRequest,
+/// Finds JSX elements with a name, optionally filtered by an attribute and
+/// value, calling `on_element` for each element found.
+pub struct ElementVisitor<'a, F: FnMut(&JSXElement)> {
+    pub name: &'a str,
+    pub attribute: Option<&'a str>,
+    pub value: Option<&'a str>,
+    pub on_element: F,
 }
 
-impl<'a> Visit for ElementVisitor<'a> {
+impl<F: FnMut(&JSXElement)> Visit for ElementVisitor<'_, F> {
     fn visit_jsx_element(&mut self, node: &JSXElement) {
         // First check that the element name matches the request
-        if get_element_name(node) == self.request.name {
+        if get_element_name(node) == self.name {
             // If an attribute is provided, check that the element contains
             // the given attribute and optionally the value specified.
-            let has_attr = match &self.request.attribute {
-                Some(attr) => {
-                    has_required_attr(node, &attr, &self.request.value)
-                }
+            let has_attr = match self.attribute {
+                Some(attr) => has_required_attr(node, attr, self.value),
                 None => true,
             };
 
             if has_attr {
-                self.elements.push(node.span);
+                (self.on_element)(node);
             }
         }
 
@@ -67,7 +74,7 @@ fn get_member_name(expr: &JSXMemberExpr) -> String {
     format!("{}.{}", prefix, utils::ast::get_ident_name(&expr.prop))
 }
 
-fn get_element_name(element: &JSXElement) -> String {
+pub fn get_element_name(element: &JSXElement) -> String {
     match &element.opening.name {
         JSXElementName::Ident(ident) => utils::ast::get_ident_name(ident),
         JSXElementName::JSXMemberExpr(expr) => get_member_name(expr),
@@ -77,7 +84,7 @@ fn get_element_name(element: &JSXElement) -> String {
     }
 }
 
-fn get_attribute_name(attr: &JSXAttr) -> String {
+pub fn get_attribute_name(attr: &JSXAttr) -> String {
     match &attr.name {
         JSXAttrName::Ident(ident) => utils::ast::get_ident_name(ident),
         JSXAttrName::JSXNamespacedName(name) => {
@@ -86,33 +93,21 @@ fn get_attribute_name(attr: &JSXAttr) -> String {
     }
 }
 
-fn get_lit_value(lit: &Lit) -> String {
-    match lit {
-        Lit::Str(str) => str.value.to_string(),
-        Lit::Bool(bool) => bool.value.to_string(),
-        Lit::Num(num) => num.value.to_string(),
-        Lit::BigInt(bigint) => bigint.value.to_string(),
-        Lit::Regex(regex) => regex.exp.to_string(),
-        Lit::JSXText(text) => text.value.to_string(),
-        Lit::Null(_) => String::from("null"),
-    }
-}
-
 fn get_expr_value(expr: &JSXExpr) -> String {
     match expr {
         JSXExpr::Expr(expr) => match *expr.clone() {
-            Expr::Lit(lit) => get_lit_value(&lit),
+            Expr::Lit(lit) => utils::ast::get_lit_value(&lit),
             _ => "".to_string(),
         },
         _ => "".to_string(),
     }
 }
 
-fn get_attribute_value(attr: &JSXAttr) -> String {
+pub fn get_attribute_value(attr: &JSXAttr) -> String {
     match &attr.value {
         Some(value) => match value {
             JSXAttrValue::JSXExprContainer(expr) => get_expr_value(&expr.expr),
-            JSXAttrValue::Lit(lit) => get_lit_value(lit),
+            JSXAttrValue::Lit(lit) => utils::ast::get_lit_value(lit),
             _ => "".to_string(),
         },
         // When there is no value, it is an implicit boolean attribute with
@@ -123,17 +118,17 @@ fn get_attribute_value(attr: &JSXAttr) -> String {
 
 fn has_required_attr(
     element: &JSXElement,
-    name: &String,
-    value: &Option<String>,
+    name: &str,
+    value: Option<&str>,
 ) -> bool {
     let count = element
         .opening
         .attrs
         .iter()
         .filter_map(utils::ast::is_jsx_attribute)
-        .filter(|attr| get_attribute_name(attr) == *name)
+        .filter(|attr| get_attribute_name(attr) == name)
         .filter(|attr| match value {
-            Some(value) => get_attribute_value(attr) == *value,
+            Some(value) => get_attribute_value(attr) == value,
             None => true,
         })
         .count();
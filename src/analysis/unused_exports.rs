@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use globset::GlobSet;
+use swc_common::Loc;
+
+use crate::analysis::exports::ExportKind;
+use crate::analysis::imports::ImportKind;
+use crate::graph::resolver::Resolver;
+use crate::graph::ModuleGraph;
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+
+#[derive(Clone)]
+pub struct UnusedExportsRequest {
+    pub path: PathBuf,
+    /// Modules whose exports are public and therefore always used
+    pub entries: GlobSet,
+    /// The absolute path of the directory entry globs are relative to
+    pub root: PathBuf,
+    graph: ModuleGraph,
+}
+
+impl UnusedExportsRequest {
+    pub fn new(
+        path: PathBuf,
+        entries: GlobSet,
+        root: PathBuf,
+        resolver: Resolver,
+    ) -> UnusedExportsRequest {
+        UnusedExportsRequest {
+            path,
+            entries,
+            root,
+            graph: ModuleGraph::new(resolver),
+        }
+    }
+
+    fn is_entry(&self, module: usize) -> bool {
+        let path = &self.graph.modules[module].path;
+        self.entries.is_match(path.strip_prefix(&self.root).unwrap_or(path))
+    }
+}
+
+impl ProcessorRequest for UnusedExportsRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn uses_all_modules(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        // Whether an export is used can't be known until every module has
+        // been visited, so modules are only collected here.
+        self.graph.add(parsed);
+        vec![]
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.graph.remove(path);
+    }
+
+    fn finish(&mut self) -> Vec<(String, Loc)> {
+        let graph = &self.graph;
+        let mut usage = Usage::new(graph);
+
+        for module in 0..graph.modules.len() {
+            if self.is_entry(module) {
+                usage.mark_all(module);
+            }
+
+            // Re-exports are only used when the barrel file exporting them is
+            // used, which is handled when marking the barrel's exports.
+            graph.modules[module]
+                .imports
+                .iter()
+                .filter(|import| import.kind != ImportKind::ReExport)
+                .for_each(|import| {
+                    if let Some(target) = graph.resolve(module, &import.source)
+                    {
+                        import
+                            .names
+                            .iter()
+                            .for_each(|name| usage.mark(target, name));
+                    }
+                });
+        }
+
+        graph
+            .sorted()
+            .into_iter()
+            .filter(|index| !usage.all.contains(index))
+            .flat_map(|index| {
+                let module = &graph.modules[index];
+
+                module
+                    .exports
+                    .iter()
+                    .filter(|export| match export.kind {
+                        ExportKind::ReExportAll { .. } => false,
+                        _ => export.name != "=",
+                    })
+                    .filter(|export| {
+                        !usage.used.contains(&(index, export.name.clone()))
+                    })
+                    .filter_map(|export| {
+                        module.locate(export.span, Some(export.to_string()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Tracks which exports of each module are used. Using an export which is
+/// re-exported from another module also uses the original export, so barrel
+/// files are followed to the module which declares the export.
+struct Usage<'a> {
+    graph: &'a ModuleGraph,
+    used: HashSet<(usize, String)>,
+    /// Modules which have all of their exports used, such as entry points and
+    /// modules imported using a namespace import.
+    all: HashSet<usize>,
+}
+
+impl<'a> Usage<'a> {
+    fn new(graph: &'a ModuleGraph) -> Usage<'a> {
+        Usage { graph, used: HashSet::new(), all: HashSet::new() }
+    }
+
+    fn mark(&mut self, module: usize, name: &str) {
+        if name == "*" {
+            self.mark_all(module);
+            return;
+        }
+
+        if self.all.contains(&module)
+            || !self.used.insert((module, name.to_string()))
+        {
+            return;
+        }
+
+        let node = &self.graph.modules[module];
+        let mut declared = false;
+
+        for export in node.exports.iter().filter(|export| export.name == name) {
+            declared = true;
+
+            if let ExportKind::ReExport { source, name } = &export.kind {
+                if let Some(target) = self.graph.resolve(module, source) {
+                    self.mark(target, name);
+                }
+            }
+        }
+
+        // Names which aren't declared by the module may come from one of its
+        // `export *` re-exports. Default exports are never included in those.
+        if !declared && name != "default" {
+            for export in &node.exports {
+                if let ExportKind::ReExportAll { source } = &export.kind {
+                    if let Some(target) = self.graph.resolve(module, source) {
+                        self.mark(target, name);
+                    }
+                }
+            }
+        }
+    }
+
+    fn mark_all(&mut self, module: usize) {
+        if !self.all.insert(module) {
+            return;
+        }
+
+        for export in &self.graph.modules[module].exports {
+            match &export.kind {
+                ExportKind::ReExport { source, name } => {
+                    if let Some(target) = self.graph.resolve(module, source) {
+                        self.mark(target, name);
+                    }
+                }
+                ExportKind::ReExportAll { source } => {
+                    if let Some(target) = self.graph.resolve(module, source) {
+                        self.mark_all(target);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
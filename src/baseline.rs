@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use console::style;
+use swc_common::{FileName, Loc};
+
+use crate::logger::Logger;
+use crate::utils;
+
+/// The number of matches of each text in each file, keyed by the path
+/// relative to the working directory so the baseline can be shared.
+type Counts = BTreeMap<String, BTreeMap<String, usize>>;
+
+/// A logger which compares matches to a baseline from a previous run. Matches
+/// are collected until `finish` is called, and only matches exceeding the
+/// baseline are forwarded to the inner logger.
+pub struct BaselineLogger<L: Logger> {
+    inner: L,
+    cwd: PathBuf,
+    matches: Vec<(String, Loc)>,
+}
+
+pub enum BaselineError {
+    IO(io::Error),
+    Invalid(serde_json::Error),
+}
+
+impl std::fmt::Display for BaselineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BaselineError::IO(err) => write!(f, "{}", err),
+            BaselineError::Invalid(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<L: Logger> Logger for BaselineLogger<L> {
+    fn log(&mut self, text: String, loc: Loc) {
+        self.matches.push((text, loc));
+    }
+}
+
+impl<L: Logger> BaselineLogger<L> {
+    pub fn new(inner: L, cwd: &Path) -> BaselineLogger<L> {
+        BaselineLogger {
+            inner,
+            cwd: utils::normalize_path(cwd),
+            matches: Vec::new(),
+        }
+    }
+
+    /// Compare the collected matches to the baseline file and log the new
+    /// matches, returning the number logged. If the baseline doesn't exist,
+    /// it is created from the matches instead. When `update` is set and no
+    /// new matches were found, the baseline is rewritten so that matches
+    /// which have been removed can't be added back.
+    pub fn finish(
+        mut self,
+        path: &Path,
+        update: bool,
+    ) -> Result<usize, BaselineError> {
+        let mut counts = Counts::new();
+
+        for (text, loc) in &self.matches {
+            *counts
+                .entry(self.file_key(loc))
+                .or_default()
+                .entry(text.clone())
+                .or_default() += 1;
+        }
+
+        let baseline: Counts = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(BaselineError::Invalid)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                write_counts(path, &counts)?;
+                eprintln!(
+                    "Created baseline {} with {} matches.",
+                    path.display(),
+                    self.matches.len()
+                );
+
+                return Ok(0);
+            }
+            Err(err) => return Err(BaselineError::IO(err)),
+        };
+
+        // Matches of the same text in a file are interchangeable, so the
+        // occurrences after the number in the baseline are the new ones.
+        let mut seen: HashMap<(String, String), usize> = HashMap::new();
+        let mut new = 0;
+
+        for (text, loc) in std::mem::take(&mut self.matches) {
+            let file = self.file_key(&loc);
+            let allowed = baseline
+                .get(&file)
+                .and_then(|texts| texts.get(&text))
+                .copied()
+                .unwrap_or(0);
+
+            let count = seen.entry((file, text.clone())).or_default();
+            *count += 1;
+
+            if *count > allowed {
+                self.inner.log(text, loc);
+                new += 1;
+            }
+        }
+
+        let total = |counts: &Counts| -> usize {
+            counts.values().flat_map(|texts| texts.values()).sum()
+        };
+
+        let removed = total(&baseline) + new - total(&counts);
+
+        if new == 0 && removed > 0 {
+            if update {
+                write_counts(path, &counts)?;
+                eprintln!(
+                    "Updated baseline {} with {} fewer matches.",
+                    path.display(),
+                    removed
+                );
+            } else {
+                eprintln!(
+                    "{}: {} matches in the baseline were removed. Use --update-baseline to update it.",
+                    style("note").bold(),
+                    removed
+                );
+            }
+        }
+
+        Ok(new)
+    }
+
+    /// Get the path of the file containing a match relative to the working
+    /// directory.
+    fn file_key(&self, loc: &Loc) -> String {
+        match &loc.file.name {
+            FileName::Real(path) => {
+                let path = utils::normalize_path(path);
+
+                path.strip_prefix(&self.cwd)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string()
+            }
+            name => name.to_string(),
+        }
+    }
+}
+
+fn write_counts(path: &Path, counts: &Counts) -> Result<(), BaselineError> {
+    let mut contents =
+        serde_json::to_string_pretty(counts).map_err(BaselineError::Invalid)?;
+    contents.push('\n');
+
+    fs::write(path, contents).map_err(BaselineError::IO)
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use serde::Deserialize;
+
+use crate::analysis::imports::ImportKind;
+use crate::logger::OutputFormat;
+use crate::parser::ParserOptions;
+use crate::utils;
+
+/// The name of the project configuration file
+pub const CONFIG_FILE: &str = "flashlight.toml";
+
+/// Project configuration read from `flashlight.toml`. Globs and paths are
+/// relative to the directory containing the config file.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The default output format
+    pub format: Option<OutputFormat>,
+    /// Globs matching the files to search. All files are searched if empty.
+    pub include: Vec<String>,
+    /// Globs matching files to skip
+    pub exclude: Vec<String>,
+    /// The extensions of the files to search (e.g., ts). Defaults to the
+    /// JavaScript and TypeScript extensions.
+    pub extensions: Vec<String>,
+    pub parser: ParserOptions,
+    /// The `tsconfig.json` file whose path aliases are used to resolve imports
+    pub tsconfig: Option<PathBuf>,
+    /// Globs matching entry point modules whose exports are public
+    pub entries: Vec<String>,
+    /// Saved queries which can be run by name, each being the arguments of a
+    /// command (e.g., `["imports", "moment"]`)
+    pub queries: BTreeMap<String, Vec<String>>,
+    pub boundaries: Vec<Boundary>,
+    pub rules: Vec<Rule>,
+    /// The directory containing the config file
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+/// An architecture boundary restricting which modules can import each other
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Boundary {
+    /// Glob matching the importing modules
+    pub from: String,
+    /// Glob matching the imported modules
+    pub to: String,
+    /// Allow the imports instead of denying them. Boundaries are evaluated
+    /// in order and the first matching boundary is used, so allowed
+    /// boundaries can be used as exceptions to later denied boundaries.
+    #[serde(default)]
+    pub allow: bool,
+    /// Only apply the boundary to imports of modules in another package
+    #[serde(default)]
+    pub cross_package: bool,
+    /// The message reported for imports denied by the boundary
+    pub message: Option<String>,
+}
+
+/// A rule forbidding imports matching a query
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Glob matching the import source (e.g., moment)
+    pub source: String,
+    /// Only match imports of this name. Use `default` for default imports or
+    /// `*` for namespace imports
+    pub specifier: Option<String>,
+    /// Only match this kind of import
+    pub kind: Option<ImportKind>,
+    #[serde(default)]
+    pub severity: Severity,
+    /// The message reported for matching imports (e.g., use date-fns)
+    pub message: String,
+}
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Matches fail the check
+    #[default]
+    Error,
+    /// Matches are reported without failing the check
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+pub enum ConfigError {
+    IO(io::Error),
+    Parse(toml::de::Error),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigError::IO(err) => write!(f, "{}", err),
+            ConfigError::Parse(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// Find the config file in a directory or the nearest parent directory. Paths
+/// are absolutized rather than canonicalized, like the paths of modules, so
+/// globs relative to the config file match them through symlinks.
+pub fn find(dir: &Path) -> Option<PathBuf> {
+    let dir = utils::absolutize(dir);
+
+    dir.ancestors().map(|dir| dir.join(CONFIG_FILE)).find(|path| path.is_file())
+}
+
+pub fn load(path: &Path) -> Result<Config, ConfigError> {
+    let text = fs::read_to_string(path).map_err(ConfigError::IO)?;
+    let mut config: Config =
+        toml::from_str(&text).map_err(ConfigError::Parse)?;
+
+    config.dir = utils::absolutize(path)
+        .parent()
+        .map_or_else(PathBuf::new, Path::to_path_buf);
+
+    Ok(config)
+}
+
+/// Load a file of queries, each being the arguments of a command keyed by its
+/// name like the saved queries of the config file
+pub fn load_queries(
+    path: &Path,
+) -> Result<BTreeMap<String, Vec<String>>, ConfigError> {
+    let text = fs::read_to_string(path).map_err(ConfigError::IO)?;
+
+    toml::from_str(&text).map_err(ConfigError::Parse)
+}
@@ -0,0 +1,325 @@
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::Path;
+
+use console::style;
+use swc_common::{BytePos, SourceFile, Span, DUMMY_SP};
+
+/// The number of unchanged lines shown around changes in diffs
+const CONTEXT: usize = 3;
+
+/// How edits made by requests which change files are applied
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum EditMode {
+    /// Write the edits to files
+    #[default]
+    Write,
+    /// Print a unified diff of the edits instead of writing them
+    DryRun,
+    /// Log the edits without writing them
+    Check,
+    /// Prompt before writing each hunk of edits
+    Interactive,
+}
+
+/// A replacement of the text in a span of a module. Insertions use an empty
+/// span and deletions use empty text.
+pub struct Edit {
+    pub span: Span,
+    pub text: String,
+}
+
+impl Edit {
+    pub fn replace(span: Span, text: impl Into<String>) -> Edit {
+        Edit { span, text: text.into() }
+    }
+
+    pub fn insert(pos: BytePos, text: impl Into<String>) -> Edit {
+        Edit { span: Span { lo: pos, hi: pos, ..DUMMY_SP }, text: text.into() }
+    }
+
+    pub fn delete(span: Span) -> Edit {
+        Edit { span, text: String::new() }
+    }
+}
+
+/// Get the source text of a span
+pub fn source_text(file: &SourceFile, span: Span) -> &str {
+    let lo = (span.lo - file.start_pos).0 as usize;
+    let hi = (span.hi - file.start_pos).0 as usize;
+
+    &file.src[lo..hi]
+}
+
+/// Extend the span of a statement to include the newline after it, so that
+/// deleting it doesn't leave an empty line
+pub fn line_span(file: &SourceFile, span: Span) -> Span {
+    let hi = (span.hi - file.start_pos).0 as usize;
+
+    match file.src[hi..].starts_with('\n') {
+        true => Span { hi: span.hi + BytePos(1), ..span },
+        false => span,
+    }
+}
+
+/// Apply edits to the source of a file. Text outside of the edited spans is
+/// kept as is, so the formatting of unchanged code is preserved. The edits
+/// must be sorted and can't overlap.
+pub fn apply<'a>(
+    file: &SourceFile,
+    edits: impl IntoIterator<Item = &'a Edit>,
+) -> String {
+    splice(file, 0..file.src.len(), edits)
+}
+
+/// Apply the sorted edits within a span to its source text. Edits outside of
+/// the span are ignored.
+pub fn apply_to_span<'a>(
+    file: &SourceFile,
+    span: Span,
+    edits: impl IntoIterator<Item = &'a Edit>,
+) -> String {
+    let lo = (span.lo - file.start_pos).0 as usize;
+    let hi = (span.hi - file.start_pos).0 as usize;
+
+    splice(
+        file,
+        lo..hi,
+        edits
+            .into_iter()
+            .filter(|edit| span.lo <= edit.span.lo && edit.span.hi <= span.hi),
+    )
+}
+
+/// Find the first edit overlapping a previous edit. The edits must be sorted.
+/// Insertions at the same position don't overlap, and are applied in order.
+pub fn find_overlap(edits: &[Edit]) -> Option<&Edit> {
+    let mut hi: Option<BytePos> = None;
+
+    edits.iter().find(|edit| {
+        let overlaps = hi.is_some_and(|hi| edit.span.lo < hi);
+        hi = Some(hi.map_or(edit.span.hi, |hi| hi.max(edit.span.hi)));
+
+        overlaps
+    })
+}
+
+/// A group of nearby edits shown as a single hunk of a unified diff
+pub struct Hunk {
+    /// The indexes of the edits in the hunk
+    pub edits: Range<usize>,
+    /// The index of the first line of the hunk in the original file
+    start: usize,
+    /// The lines of the hunk, prefixed with ` `, `-` or `+`
+    lines: Vec<(char, String)>,
+}
+
+impl Hunk {
+    fn count(&self, prefix: char) -> usize {
+        self.lines.iter().filter(|(p, _)| *p == ' ' || *p == prefix).count()
+    }
+
+    /// The number of lines added by the hunk, or removed when negative
+    pub fn delta(&self) -> isize {
+        self.count('+') as isize - self.count('-') as isize
+    }
+
+    /// Render the hunk, where `offset` is the number of lines added by the
+    /// hunks before it
+    pub fn render(&self, offset: isize) -> String {
+        let old = self.count('-');
+        let new = self.count('+');
+
+        // Empty ranges start at the line before the hunk
+        let start = |len: usize, offset: isize| {
+            let start = self.start as isize + offset;
+            if len == 0 {
+                start
+            } else {
+                start + 1
+            }
+        };
+
+        let mut output = format!(
+            "{}\n",
+            style(format!(
+                "@@ -{},{} +{},{} @@",
+                start(old, 0),
+                old,
+                start(new, offset),
+                new
+            ))
+            .cyan()
+        );
+
+        for (prefix, line) in &self.lines {
+            let text = format!("{}{}", prefix, line.trim_end_matches('\n'));
+            let text = match prefix {
+                '-' => style(text).red(),
+                '+' => style(text).green(),
+                _ => style(text),
+            };
+
+            let _ = writeln!(output, "{}", text);
+
+            if !line.ends_with('\n') {
+                output.push_str("\\ No newline at end of file\n");
+            }
+        }
+
+        output
+    }
+}
+
+/// Group sorted edits into hunks. Edits changing the same lines are shown
+/// together, and edits with overlapping context are merged into one hunk.
+pub fn hunks(file: &SourceFile, edits: &[Edit]) -> Vec<Hunk> {
+    let lines = get_lines(&file.src);
+    let line_of = |pos: BytePos| {
+        let pos = (pos - file.start_pos).0 as usize;
+        let line = lines.partition_point(|line| line.start <= pos);
+
+        line.saturating_sub(1).min(lines.len().saturating_sub(1))
+    };
+
+    // Group the edits by the lines they change, where edits on adjacent
+    // lines are grouped so their removed and added lines are shown together
+    let mut changes: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+
+    for (index, edit) in edits.iter().enumerate() {
+        let first = line_of(edit.span.lo);
+        let last = match edit.span.hi > edit.span.lo {
+            true => line_of(edit.span.hi - BytePos(1)),
+            false => first,
+        };
+
+        match changes.last_mut() {
+            Some((indexes, range)) if first <= range.end => {
+                indexes.end = index + 1;
+                range.end = range.end.max(last + 1);
+            }
+            _ => changes.push((index..index + 1, first..last + 1)),
+        }
+    }
+
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut end = 0;
+
+    for (indexes, range) in changes {
+        let context = range.start.saturating_sub(CONTEXT);
+
+        let hunk = match hunks.last_mut() {
+            Some(hunk) if context <= end + CONTEXT => {
+                hunk.lines.extend(
+                    (end..range.start).map(|i| (' ', line(file, &lines, i))),
+                );
+                hunk.edits.end = indexes.end;
+                hunk
+            }
+            _ => {
+                if let Some(hunk) = hunks.last_mut() {
+                    let after = (end + CONTEXT).min(lines.len());
+                    hunk.lines.extend(
+                        (end..after).map(|i| (' ', line(file, &lines, i))),
+                    );
+                }
+
+                hunks.push(Hunk {
+                    edits: indexes.clone(),
+                    start: context,
+                    lines: (context..range.start)
+                        .map(|i| (' ', line(file, &lines, i)))
+                        .collect(),
+                });
+                hunks.last_mut().unwrap()
+            }
+        };
+
+        let bytes = lines[range.start].start..lines[range.end - 1].end;
+        let new = splice(file, bytes, &edits[indexes]);
+
+        // Empty files have a single empty line, which isn't removed
+        hunk.lines.extend(
+            range
+                .clone()
+                .map(|i| ('-', line(file, &lines, i)))
+                .filter(|(_, line)| !line.is_empty()),
+        );
+        hunk.lines.extend(
+            new.split_inclusive('\n').map(|line| ('+', line.to_string())),
+        );
+        end = range.end;
+    }
+
+    if let Some(hunk) = hunks.last_mut() {
+        let after = (end + CONTEXT).min(lines.len());
+        hunk.lines.extend((end..after).map(|i| (' ', line(file, &lines, i))));
+    }
+
+    hunks
+}
+
+/// Render a unified diff of the changes made by sorted edits to a file
+pub fn render_diff(path: &Path, file: &SourceFile, edits: &[Edit]) -> String {
+    let mut output = render_header(path);
+    let mut offset = 0;
+
+    for hunk in hunks(file, edits) {
+        output.push_str(&hunk.render(offset));
+        offset += hunk.delta();
+    }
+
+    output
+}
+
+pub fn render_header(path: &Path) -> String {
+    format!(
+        "{}\n{}\n",
+        style(format!("--- {}", path.display())).bold(),
+        style(format!("+++ {}", path.display())).bold()
+    )
+}
+
+/// Get the byte ranges of the lines of a file, including their newline
+fn get_lines(src: &str) -> Vec<Range<usize>> {
+    let mut start = 0;
+    let lines: Vec<Range<usize>> = src
+        .split_inclusive('\n')
+        .map(|line| {
+            start += line.len();
+            start - line.len()..start
+        })
+        .collect();
+
+    match lines.is_empty() {
+        true => std::iter::once(0..0).collect(),
+        false => lines,
+    }
+}
+
+fn line(file: &SourceFile, lines: &[Range<usize>], index: usize) -> String {
+    file.src[lines[index].clone()].to_string()
+}
+
+/// Apply sorted edits to a range of the source of a file, returning the new
+/// text of the range
+fn splice<'a>(
+    file: &SourceFile,
+    range: Range<usize>,
+    edits: impl IntoIterator<Item = &'a Edit>,
+) -> String {
+    let mut output = String::with_capacity(range.len());
+    let mut pos = range.start;
+
+    for edit in edits {
+        let lo = (edit.span.lo - file.start_pos).0 as usize;
+        let hi = (edit.span.hi - file.start_pos).0 as usize;
+
+        output.push_str(&file.src[pos..lo]);
+        output.push_str(&edit.text);
+        pos = hi;
+    }
+
+    output.push_str(&file.src[pos..range.end]);
+    output
+}
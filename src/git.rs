@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The changes used to find changed files
+pub enum Changes {
+    /// Files changed since the merge base of a ref and the working tree,
+    /// including untracked files
+    Since(String),
+    /// Files with staged changes
+    Staged,
+}
+
+pub enum GitError {
+    IO(io::Error),
+    Failed(String),
+}
+
+impl std::fmt::Display for GitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GitError::IO(err) => write!(f, "Failed to run git: {}", err),
+            GitError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Get the canonical paths of the files added or modified in the repository
+/// containing a directory. Deleted files are excluded since they can't be
+/// searched.
+pub fn changed_files(
+    dir: &Path,
+    changes: &Changes,
+) -> Result<HashSet<PathBuf>, GitError> {
+    let root = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?);
+    let diff = ["diff", "--name-only", "--no-relative", "--diff-filter=ACMR"];
+
+    let output = match changes {
+        Changes::Since(reference) => {
+            let mut output = git(
+                &root,
+                &[&diff[..], &["--merge-base", reference, "--"]].concat(),
+            )?;
+
+            output.push('\n');
+            output.push_str(&git(
+                &root,
+                &["ls-files", "--others", "--exclude-standard"],
+            )?);
+
+            output
+        }
+        Changes::Staged => git(&root, &[&diff[..], &["--cached"]].concat())?,
+    };
+
+    Ok(output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| root.join(line))
+        .collect())
+}
+
+/// Run a git command in a directory and get its trimmed output
+fn git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "core.quotePath=off"])
+        .args(args)
+        .output()
+        .map_err(GitError::IO)?;
+
+    if !output.status.success() {
+        return Err(GitError::Failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
@@ -0,0 +1,117 @@
+pub mod package;
+pub mod resolver;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use swc_common::sync::Lrc;
+use swc_common::{Loc, SourceMap, Span};
+
+use crate::analysis::exports::{self, Export};
+use crate::analysis::imports::{self, ModuleImport};
+use crate::config::Severity;
+use crate::parser::ParsedModule;
+use crate::processor::{self, Match};
+use crate::utils;
+use resolver::Resolver;
+
+/// A module in the graph along with its imports and exports
+#[derive(Clone)]
+pub struct ModuleNode {
+    /// The absolute path of the module
+    pub path: PathBuf,
+    pub imports: Vec<ModuleImport>,
+    pub exports: Vec<Export>,
+    source_map: Lrc<SourceMap>,
+}
+
+impl ModuleNode {
+    /// Get the location and text to log for a span in this module
+    pub fn locate(
+        &self,
+        span: Span,
+        text: Option<String>,
+    ) -> Option<(String, Loc)> {
+        processor::locate(
+            &self.source_map,
+            Match { span, text, severity: Severity::Error },
+        )
+    }
+}
+
+/// The graph of modules visited by the processor. Modules are stored in the
+/// order they were visited, unless modules were removed, and edges are
+/// resolved once all modules have been added since an import may refer to a
+/// module which hasn't been visited yet.
+#[derive(Clone, Default)]
+pub struct ModuleGraph {
+    pub modules: Vec<ModuleNode>,
+    pub resolver: Resolver,
+    index: HashMap<PathBuf, usize>,
+}
+
+impl ModuleGraph {
+    pub fn new(resolver: Resolver) -> ModuleGraph {
+        ModuleGraph { resolver, ..Default::default() }
+    }
+
+    /// Add a module to the graph and get its index
+    pub fn add(&mut self, parsed: &ParsedModule) -> usize {
+        let path = utils::absolutize(&parsed.path);
+        let module = self.modules.len();
+
+        self.index.insert(path.clone(), module);
+        self.modules.push(ModuleNode {
+            path,
+            imports: imports::get_module_imports(&parsed.module),
+            exports: exports::get_exports(&parsed.module),
+            source_map: parsed.source_map.clone(),
+        });
+
+        module
+    }
+
+    /// Remove a module from the graph. The last module takes its place, so
+    /// the indices of other modules stay valid.
+    pub fn remove(&mut self, path: &Path) {
+        let module = match self.index.remove(&utils::absolutize(path)) {
+            Some(module) => module,
+            None => return,
+        };
+
+        self.modules.swap_remove(module);
+
+        if let Some(moved) = self.modules.get(module) {
+            self.index.insert(moved.path.clone(), module);
+        }
+    }
+
+    /// Resolve an import source from a module to another module in the graph
+    pub fn resolve(&self, from: usize, source: &str) -> Option<usize> {
+        let path = self.resolver.resolve(&self.modules[from].path, source)?;
+        self.index.get(&path).copied()
+    }
+
+    /// Get the modules imported by a module along with the index of the
+    /// import. Imports which don't resolve to a module in the graph are
+    /// skipped.
+    pub fn dependencies(
+        &self,
+        module: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.modules[module].imports.iter().enumerate().filter_map(
+            move |(index, import)| {
+                Some((self.resolve(module, &import.source)?, index))
+            },
+        )
+    }
+
+    /// Get the indices of all modules sorted by path. Modules are added in the
+    /// order of the file system, so this is used to keep output stable.
+    pub fn sorted(&self) -> Vec<usize> {
+        let mut modules: Vec<usize> = (0..self.modules.len()).collect();
+        modules
+            .sort_by(|a, b| self.modules[*a].path.cmp(&self.modules[*b].path));
+        modules
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use serde::Deserialize;
+
+/// The parts of a `package.json` manifest used by Flashlight
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Manifest {
+    pub name: Option<String>,
+    pub dependencies: BTreeMap<String, String>,
+    pub dev_dependencies: BTreeMap<String, String>,
+    pub peer_dependencies: BTreeMap<String, String>,
+    pub optional_dependencies: BTreeMap<String, String>,
+}
+
+pub struct Package {
+    /// The directory containing the `package.json` file
+    pub dir: PathBuf,
+    pub manifest: Manifest,
+    /// Whether the manifest could be parsed. Invalid manifests have an empty
+    /// manifest instead.
+    pub is_valid: bool,
+}
+
+/// Finds the package each file belongs to. Lookups are cached by directory
+/// since most files in a package share their parent directories.
+#[derive(Clone, Default)]
+pub struct Packages {
+    dirs: HashMap<PathBuf, Option<Rc<Package>>>,
+    /// Errors for manifests which couldn't be parsed
+    pub errors: Vec<String>,
+}
+
+impl Packages {
+    /// Find the nearest package containing the given file
+    pub fn find(&mut self, file: &Path) -> Option<Rc<Package>> {
+        self.find_dir(file.parent()?)
+    }
+
+    fn find_dir(&mut self, dir: &Path) -> Option<Rc<Package>> {
+        if let Some(package) = self.dirs.get(dir) {
+            return package.clone();
+        }
+
+        let manifest_path = dir.join("package.json");
+        let package = match fs::read_to_string(&manifest_path) {
+            // Invalid manifests are still treated as the package boundary
+            Ok(contents) => {
+                Some(Rc::new(match serde_json::from_str(&contents) {
+                    Ok(manifest) => Package {
+                        dir: dir.to_path_buf(),
+                        manifest,
+                        is_valid: true,
+                    },
+                    Err(err) => {
+                        self.errors.push(format!(
+                            "Invalid manifest {}: {}",
+                            manifest_path.display(),
+                            err
+                        ));
+
+                        Package {
+                            dir: dir.to_path_buf(),
+                            manifest: Manifest::default(),
+                            is_valid: false,
+                        }
+                    }
+                }))
+            }
+            Err(_) => match dir.parent() {
+                Some(parent) => self.find_dir(parent),
+                None => None,
+            },
+        };
+
+        self.dirs.insert(dir.to_path_buf(), package.clone());
+        package
+    }
+}
+
+/// Get the package name of a bare import source, removing any subpath (e.g.,
+/// `lodash/get` is `lodash` and `@scope/pkg/x` is `@scope/pkg`).
+pub fn package_name(source: &str) -> &str {
+    let mut parts = source.splitn(3, '/');
+    let first = parts.next().unwrap_or(source);
+
+    match (first.starts_with('@'), parts.next()) {
+        (true, Some(name)) => &source[..first.len() + 1 + name.len()],
+        _ => first,
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use serde::Deserialize;
+
+use crate::utils;
+
+/// Extensions tried when resolving an import without an extension, in order
+/// of preference.
+const EXTENSIONS: [&str; 8] =
+    ["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+/// Resolves import sources to files. Relative and absolute imports are always
+/// resolved, and path aliases are resolved when loaded from a `tsconfig.json`
+/// file. Other bare specifiers such as `react` are external to the project.
+#[derive(Clone, Default)]
+pub struct Resolver {
+    /// The directory bare specifiers are resolved from (`baseUrl`)
+    base_url: Option<PathBuf>,
+    /// The directory path alias targets are relative to
+    paths_dir: PathBuf,
+    /// Path alias patterns and their targets (e.g., `@/*` to `src/*`)
+    paths: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct TsConfig {
+    compiler_options: CompilerOptions,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct CompilerOptions {
+    base_url: Option<String>,
+    paths: BTreeMap<String, Vec<String>>,
+}
+
+pub enum TsConfigError {
+    IO(io::Error),
+    Invalid(serde_json::Error),
+}
+
+impl std::fmt::Display for TsConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TsConfigError::IO(err) => write!(f, "{}", err),
+            TsConfigError::Invalid(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Resolver {
+    /// Create a resolver using the `baseUrl` and `paths` options of a
+    /// `tsconfig.json` file. Configs referenced with `extends` are not loaded.
+    pub fn from_tsconfig(path: &Path) -> Result<Resolver, TsConfigError> {
+        let text = fs::read_to_string(path).map_err(TsConfigError::IO)?;
+        let tsconfig: TsConfig =
+            serde_json::from_str(&strip_json_comments(&text))
+                .map_err(TsConfigError::Invalid)?;
+
+        let dir = utils::absolutize(path.parent().unwrap_or(Path::new("")));
+        let options = tsconfig.compiler_options;
+        let base_url =
+            options.base_url.map(|url| utils::normalize_path(&dir.join(url)));
+
+        Ok(Resolver {
+            paths_dir: base_url.clone().unwrap_or(dir),
+            base_url,
+            paths: options.paths,
+        })
+    }
+
+    /// Resolve an import source from a module to a file
+    pub fn resolve(&self, from: &Path, source: &str) -> Option<PathBuf> {
+        if is_relative(source) {
+            let dir = from.parent().unwrap_or(Path::new(""));
+            return resolve_path(&utils::normalize_path(&dir.join(source)));
+        }
+
+        self.resolve_alias(source)
+            .or_else(|| resolve_path(&self.base_url.as_ref()?.join(source)))
+    }
+
+    /// Resolve a source using the path alias with the longest matching prefix
+    fn resolve_alias(&self, source: &str) -> Option<PathBuf> {
+        let (wildcard, targets) = self
+            .paths
+            .iter()
+            .filter_map(|(pattern, targets)| {
+                let (prefix, wildcard) = match pattern.split_once('*') {
+                    Some((prefix, suffix)) => {
+                        let wildcard = source
+                            .strip_prefix(prefix)?
+                            .strip_suffix(suffix)?;
+
+                        (prefix, wildcard)
+                    }
+                    None if pattern == source => (pattern.as_str(), ""),
+                    None => return None,
+                };
+
+                Some((prefix.len(), wildcard, targets))
+            })
+            .max_by_key(|(len, _, _)| *len)
+            .map(|(_, wildcard, targets)| (wildcard, targets))?;
+
+        targets.iter().find_map(|target| {
+            let path = self.paths_dir.join(target.replacen('*', wildcard, 1));
+            resolve_path(&utils::normalize_path(&path))
+        })
+    }
+}
+
+/// Check if an import source is relative to the importing file
+pub fn is_relative(source: &str) -> bool {
+    source.starts_with("./")
+        || source.starts_with("../")
+        || source.starts_with('/')
+        || source == "."
+        || source == ".."
+}
+
+/// Resolve a path to a file by trying extensions and index files
+pub fn resolve_path(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    // TypeScript allows importing `.ts` files using the `.js` extension
+    // they will be compiled to.
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        let alternatives: &[&str] = match ext {
+            "js" => &["ts", "tsx"],
+            "jsx" => &["tsx"],
+            "mjs" => &["mts"],
+            "cjs" => &["cts"],
+            _ => &[],
+        };
+
+        if let Some(path) = alternatives
+            .iter()
+            .map(|ext| path.with_extension(ext))
+            .find(|path| path.is_file())
+        {
+            return Some(path);
+        }
+    }
+
+    with_extensions(path).or_else(|| with_extensions(&path.join("index")))
+}
+
+fn with_extensions(path: &Path) -> Option<PathBuf> {
+    EXTENSIONS
+        .iter()
+        .map(|ext| {
+            let mut path = path.as_os_str().to_owned();
+            path.push(".");
+            path.push(ext);
+            PathBuf::from(path)
+        })
+        .find(|path| path.is_file())
+}
+
+/// Remove comments and trailing commas from JSON, which are allowed in
+/// `tsconfig.json` files.
+fn strip_json_comments(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                output.push(c);
+                copy_string(&mut chars, &mut output);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+
+                while let Some(c) = chars.next() {
+                    if c == '*' && chars.next_if_eq(&'/').is_some() {
+                        break;
+                    }
+                }
+            }
+            c => output.push(c),
+        }
+    }
+
+    // Trailing commas are removed once comments can't separate them from
+    // the closing bracket.
+    let mut chars = output.chars().peekable();
+    let mut stripped = String::with_capacity(output.len());
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                stripped.push(c);
+                copy_string(&mut chars, &mut stripped);
+            }
+            ',' => {
+                let next = chars.clone().find(|c| !c.is_whitespace());
+
+                if !matches!(next, Some('}' | ']')) {
+                    stripped.push(c);
+                }
+            }
+            c => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+/// Copy the rest of a JSON string after its opening quote
+fn copy_string(chars: &mut impl Iterator<Item = char>, output: &mut String) {
+    while let Some(c) = chars.next() {
+        output.push(c);
+
+        match c {
+            '\\' => output.extend(chars.next()),
+            '"' => break,
+            _ => {}
+        }
+    }
+}
@@ -1,16 +1,49 @@
 use console::style;
+use serde::Deserialize;
 use swc_common::Loc;
 
+use crate::config::Severity;
 use crate::utils::absolute_path;
 
+#[derive(clap::ValueEnum, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// The default console format
+    Default,
+    /// Formats the output as JSON
+    Json,
+    /// Formats the output as a Vim quickfix list
+    #[value(alias("vi"))]
+    #[serde(alias = "vi")]
+    Quickfix,
+}
+
 pub trait Logger {
     fn log(&mut self, text: String, loc: Loc);
+
+    /// Log a match of a named query when running several queries at once.
+    /// By default, the name is prepended to the text.
+    fn log_labeled(&mut self, label: &str, text: String, loc: Loc) {
+        self.log(format!("[{}] {}", label, text), loc);
+    }
+
+    /// Log a match along with its severity. Requests reporting warnings
+    /// include the severity in the text, so it is ignored by default.
+    fn log_with_severity(&mut self, text: String, loc: Loc, _: Severity) {
+        self.log(text, loc);
+    }
 }
 
 pub struct ConsoleLogger;
 pub struct QuickfixLogger;
 pub struct JsonLogger;
 
+/// Collects matches in the order they were logged instead of printing them
+#[derive(Default)]
+pub struct BufferLogger {
+    pub matches: Vec<(String, Loc, Severity)>,
+}
+
 pub enum LoggerType {
     Console(ConsoleLogger),
     Quickfix(QuickfixLogger),
@@ -25,6 +58,31 @@ impl Logger for LoggerType {
             LoggerType::Json(logger) => logger.log(text, loc),
         }
     }
+
+    fn log_labeled(&mut self, label: &str, text: String, loc: Loc) {
+        match self {
+            LoggerType::Console(logger) => logger.log_labeled(label, text, loc),
+            LoggerType::Quickfix(logger) => {
+                logger.log_labeled(label, text, loc)
+            }
+            LoggerType::Json(logger) => logger.log_labeled(label, text, loc),
+        }
+    }
+}
+
+impl Logger for BufferLogger {
+    fn log(&mut self, text: String, loc: Loc) {
+        self.matches.push((text, loc, Severity::Error));
+    }
+
+    fn log_with_severity(
+        &mut self,
+        text: String,
+        loc: Loc,
+        severity: Severity,
+    ) {
+        self.matches.push((text, loc, severity));
+    }
 }
 
 impl ConsoleLogger {
@@ -67,16 +125,60 @@ impl JsonLogger {
     pub fn new() -> JsonLogger {
         JsonLogger
     }
+
+    /// Log a match along with an event describing it, such as whether it
+    /// was added or removed in watch mode
+    pub fn log_event(&mut self, event: &str, text: String, loc: Loc) {
+        println!(
+            "{{\"event\": \"{}\", {}}}",
+            escape_json(event),
+            format_json_fields(text, loc)
+        );
+    }
 }
 
 impl Logger for JsonLogger {
     fn log(&mut self, text: String, loc: Loc) {
+        println!("{{{}}}", format_json_fields(text, loc));
+    }
+
+    fn log_labeled(&mut self, label: &str, text: String, loc: Loc) {
         println!(
-            "{{\"file\": \"{}\", \"line\": {}, \"column\": {}, \"text\": \"{}\"}}",
-            absolute_path(loc.file),
-            loc.line,
-            loc.col.0 + 1,
-            text,
+            "{{\"query\": \"{}\", {}}}",
+            escape_json(label),
+            format_json_fields(text, loc)
         );
     }
 }
+
+/// Format the fields of a match as the members of a JSON object
+fn format_json_fields(text: String, loc: Loc) -> String {
+    format!(
+        "\"file\": \"{}\", \"line\": {}, \"column\": {}, \"text\": \"{}\"",
+        escape_json(&absolute_path(loc.file)),
+        loc.line,
+        loc.col.0 + 1,
+        escape_json(&text),
+    )
+}
+
+/// Escape a string for use in a JSON string literal
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {
+                escaped.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
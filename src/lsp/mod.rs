@@ -0,0 +1,402 @@
+pub mod requests;
+mod rpc;
+
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use console::style;
+use serde_json::{json, Value};
+use swc_common::{FileName, Loc};
+
+use crate::config::Severity;
+use crate::logger::BufferLogger;
+use crate::processor::{Processor, ProcessorRequest};
+use crate::{utils, Commands, Roots, Settings};
+use requests::{ImportersRequest, SymbolsRequest};
+
+/// The JSON-RPC error code for unknown methods
+const METHOD_NOT_FOUND: i64 = -32601;
+/// The JSON-RPC error code for invalid parameters
+const INVALID_PARAMS: i64 = -32602;
+
+/// LSP diagnostic severities
+const ERROR: u8 = 1;
+const WARNING: u8 = 2;
+const INFORMATION: u8 = 3;
+
+/// The LSP message type of errors shown to the user
+const ERROR_MESSAGE: u8 = 1;
+
+/// LSP symbol kinds
+const MODULE: u8 = 2;
+const CLASS: u8 = 5;
+
+type RequestResult = Result<Value, (i64, String)>;
+
+/// Run a language server over stdio until the client exits, returning the
+/// exit code. Matches of the check command and saved queries are published as
+/// diagnostics whenever a file is saved.
+pub fn serve(cwd: String, settings: &Settings) -> i32 {
+    let mut server = Server {
+        cwd,
+        settings,
+        output: io::stdout(),
+        published: HashSet::new(),
+        shutdown: false,
+    };
+
+    let mut input = io::stdin().lock();
+
+    loop {
+        let message = match rpc::read_message(&mut input) {
+            Ok(Some(message)) => message,
+            Ok(None) => return 1,
+            Err(err) => {
+                eprintln!("{}: {}", style("error").red().bold(), err);
+                return 2;
+            }
+        };
+
+        // Responses to requests sent by the server have no method
+        let method = match message.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => continue,
+        };
+
+        let params = message.get("params").unwrap_or(&Value::Null);
+        let result = match message.get("id") {
+            Some(id) => {
+                let response = match server.handle_request(method, params) {
+                    Ok(result) => {
+                        json!({ "jsonrpc": "2.0", "id": id, "result": result })
+                    }
+                    Err((code, message)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": message },
+                    }),
+                };
+
+                server.send(&response)
+            }
+            None if method == "exit" => return i32::from(!server.shutdown),
+            None => server.handle_notification(method),
+        };
+
+        if let Err(err) = result {
+            eprintln!("{}: {}", style("error").red().bold(), err);
+            return 2;
+        }
+    }
+}
+
+struct Server<'a> {
+    cwd: String,
+    settings: &'a Settings,
+    output: io::Stdout,
+    /// The documents with published diagnostics, which must be cleared when
+    /// their diagnostics are resolved
+    published: HashSet<String>,
+    shutdown: bool,
+}
+
+impl Server<'_> {
+    fn send(&mut self, message: &Value) -> io::Result<()> {
+        rpc::write_message(&mut self.output.lock(), message)
+    }
+
+    fn handle_request(
+        &mut self,
+        method: &str,
+        params: &Value,
+    ) -> RequestResult {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": { "openClose": true, "save": true },
+                    "workspaceSymbolProvider": true,
+                },
+                "serverInfo": {
+                    "name": "flashlight",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "workspace/symbol" => self.symbols(params),
+            "flashlight/importers" => self.importers(params),
+            method => {
+                Err((METHOD_NOT_FOUND, format!("Unknown method {}", method)))
+            }
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str) -> io::Result<()> {
+        match method {
+            "initialized" | "textDocument/didSave" => {
+                self.publish_diagnostics()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Find import sources and JSX tags containing the query
+    fn symbols(&self, params: &Value) -> RequestResult {
+        let request = SymbolsRequest {
+            path: PathBuf::from(&self.cwd),
+            query: params["query"].as_str().unwrap_or_default().to_string(),
+        };
+
+        Ok(self
+            .process(request)
+            .into_iter()
+            .filter_map(|(name, loc)| {
+                let kind = if name.starts_with('<') { CLASS } else { MODULE };
+
+                Some(json!({
+                    "name": name,
+                    "kind": kind,
+                    "location": location(&loc)?,
+                }))
+            })
+            .collect())
+    }
+
+    /// Find the imports of the module in a text document
+    fn importers(&self, params: &Value) -> RequestResult {
+        let target = params["textDocument"]["uri"]
+            .as_str()
+            .and_then(uri_to_path)
+            .ok_or((INVALID_PARAMS, "Expected a file URI".to_string()))?;
+
+        let request = ImportersRequest::new(
+            PathBuf::from(&self.cwd),
+            utils::absolutize(&target),
+            self.settings.resolver.clone(),
+        );
+
+        Ok(self
+            .process(request)
+            .iter()
+            .filter_map(|(_, loc)| location(loc))
+            .collect())
+    }
+
+    fn process<R: ProcessorRequest>(&self, request: R) -> Vec<(String, Loc)> {
+        let mut logger = BufferLogger::default();
+        Processor::new(request, &mut logger, &self.settings.options).process();
+
+        logger.matches.into_iter().map(|(text, loc, _)| (text, loc)).collect()
+    }
+
+    /// Show an error to the user. Errors printed to stderr are only logged by
+    /// most clients.
+    fn show_error(&mut self, message: String) -> io::Result<()> {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": "window/showMessage",
+            "params": { "type": ERROR_MESSAGE, "message": message },
+        }))
+    }
+
+    /// Run the check command and saved queries, publishing their matches as
+    /// diagnostics. Documents whose diagnostics were all resolved are sent an
+    /// empty list to clear them. Queries which fail are shown as errors
+    /// without stopping the server.
+    fn publish_diagnostics(&mut self) -> io::Result<()> {
+        let mut diagnostics: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+
+        for (code, command) in self.commands()? {
+            let is_check = matches!(command, Commands::Check { .. });
+            let request = match crate::build_request(
+                command,
+                &self.cwd,
+                self.settings,
+                &mut vec![],
+            ) {
+                Ok(request) => request,
+                Err(err) => {
+                    self.show_error(format!("Query {} failed: {}", code, err))?;
+                    continue;
+                }
+            };
+
+            let mut logger = BufferLogger::default();
+            Processor::new(request, &mut logger, &self.settings.options)
+                .process();
+
+            for (text, loc, severity) in logger.matches {
+                let uri = match &loc.file.name {
+                    FileName::Real(path) => path_to_uri(path),
+                    _ => continue,
+                };
+
+                let severity = match (is_check, severity) {
+                    (false, _) => INFORMATION,
+                    (true, Severity::Warning) => WARNING,
+                    (true, Severity::Error) => ERROR,
+                };
+
+                diagnostics.entry(uri).or_default().push(json!({
+                    "range": range(&loc),
+                    "severity": severity,
+                    "code": code,
+                    "source": "flashlight",
+                    "message": text,
+                }));
+            }
+        }
+
+        let resolved: Vec<String> = self
+            .published
+            .iter()
+            .filter(|uri| !diagnostics.contains_key(*uri))
+            .cloned()
+            .collect();
+
+        for uri in resolved {
+            diagnostics.insert(uri, vec![]);
+        }
+
+        self.published.clear();
+
+        for (uri, diagnostics) in diagnostics {
+            if !diagnostics.is_empty() {
+                self.published.insert(uri.clone());
+            }
+
+            self.send(&json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/publishDiagnostics",
+                "params": { "uri": uri, "diagnostics": diagnostics },
+            }))?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the commands whose matches are reported as diagnostics along with
+    /// their diagnostic code. The check command is included when the config
+    /// file has boundaries or rules. Saved queries which don't report
+    /// matches, such as graphs, or which change files are skipped, as are
+    /// batches and queries of other queries.
+    fn commands(&mut self) -> io::Result<Vec<(String, Commands)>> {
+        let config = match &self.settings.config {
+            Some(config) => config,
+            None => return Ok(vec![]),
+        };
+
+        let mut commands = Vec::new();
+
+        if !config.boundaries.is_empty() || !config.rules.is_empty() {
+            commands.push((
+                "check".to_string(),
+                Commands::Check { roots: Roots::default() },
+            ));
+        }
+
+        for (name, args) in &config.queries {
+            match crate::parse_query(args) {
+                Ok(
+                    Commands::Graph { .. }
+                    | Commands::Run { .. }
+                    | Commands::Replace { .. }
+                    | Commands::RewriteImports { .. }
+                    | Commands::RewriteTags { .. }
+                    | Commands::Batch { .. }
+                    | Commands::Query { .. }
+                    | Commands::Lsp
+                    | Commands::UnusedModules,
+                ) => {}
+                Ok(command) => commands.push((name.clone(), command)),
+                Err(err) => {
+                    self.show_error(format!("Invalid query {}: {}", name, err))?
+                }
+            }
+        }
+
+        Ok(commands)
+    }
+}
+
+/// Get the LSP position of a location. Columns are counted in characters,
+/// while LSP uses UTF-16 code units.
+fn position(loc: &Loc) -> Value {
+    let line = loc.file.get_line(loc.line - 1).unwrap_or_default();
+    let character: usize =
+        line.chars().take(loc.col.0).map(char::len_utf16).sum();
+
+    json!({ "line": loc.line - 1, "character": character })
+}
+
+/// Get the range from a location to the end of its line
+fn range(loc: &Loc) -> Value {
+    let line = loc.file.get_line(loc.line - 1).unwrap_or_default();
+    let end = line.trim_end().encode_utf16().count();
+    let start = position(loc);
+
+    json!({
+        "start": start,
+        "end": {
+            "line": loc.line - 1,
+            "character": end.max(start["character"].as_u64().unwrap_or(0) as usize),
+        },
+    })
+}
+
+fn location(loc: &Loc) -> Option<Value> {
+    match &loc.file.name {
+        FileName::Real(path) => Some(json!({
+            "uri": path_to_uri(path),
+            "range": range(loc),
+        })),
+        _ => None,
+    }
+}
+
+/// Convert a path to a `file` URI, percent-encoding reserved characters
+fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+
+    for byte in utils::absolutize(path).to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'/'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~' => uri.push(byte as char),
+            byte => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+
+    while index < encoded.len() {
+        match encoded[index] {
+            b'%' => {
+                let hex =
+                    std::str::from_utf8(encoded.get(index + 1..index + 3)?);
+                decoded.push(u8::from_str_radix(hex.ok()?, 16).ok()?);
+                index += 3;
+            }
+            byte => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
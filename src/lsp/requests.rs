@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use swc_common::{Loc, Span};
+use swc_ecma_ast::JSXElement;
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::analysis::{imports, tags};
+use crate::config::Severity;
+use crate::graph::resolver::Resolver;
+use crate::graph::ModuleGraph;
+use crate::parser::ParsedModule;
+use crate::processor::{Match, ProcessorRequest};
+
+/// Finds import sources and JSX tags containing a query for workspace symbol
+/// search. The text of each match is the symbol name, where tags are written
+/// as `<Name>` to tell them apart from import sources.
+#[derive(Clone)]
+pub struct SymbolsRequest {
+    pub path: PathBuf,
+    pub query: String,
+}
+
+impl ProcessorRequest for SymbolsRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        let query = self.query.to_lowercase();
+        let is_match = |name: &str| name.to_lowercase().contains(&query);
+
+        let mut visitor = TagVisitor { tags: Vec::new() };
+        visitor.visit_module(&parsed.module);
+
+        let imports = imports::get_module_imports(&parsed.module)
+            .into_iter()
+            .map(|import| (import.source, import.span));
+        let tags = visitor
+            .tags
+            .into_iter()
+            .map(|(name, span)| (format!("<{}>", name), span));
+
+        imports
+            .chain(tags)
+            .filter(|(name, _)| is_match(name))
+            .map(|(name, span)| Match {
+                span,
+                text: Some(name),
+                severity: Severity::Error,
+            })
+            .collect()
+    }
+}
+
+struct TagVisitor {
+    tags: Vec<(String, Span)>,
+}
+
+impl Visit for TagVisitor {
+    fn visit_jsx_element(&mut self, node: &JSXElement) {
+        self.tags.push((tags::get_element_name(node), node.opening.span));
+        node.visit_children_with(self)
+    }
+}
+
+/// Finds the imports resolving to a module
+#[derive(Clone)]
+pub struct ImportersRequest {
+    pub path: PathBuf,
+    /// The absolute path of the imported module
+    pub target: PathBuf,
+    graph: ModuleGraph,
+}
+
+impl ImportersRequest {
+    pub fn new(
+        path: PathBuf,
+        target: PathBuf,
+        resolver: Resolver,
+    ) -> ImportersRequest {
+        ImportersRequest { path, target, graph: ModuleGraph::new(resolver) }
+    }
+}
+
+impl ProcessorRequest for ImportersRequest {
+    fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn uses_all_modules(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        self.graph.add(parsed);
+        vec![]
+    }
+
+    fn finish(&mut self) -> Vec<(String, Loc)> {
+        let graph = &self.graph;
+
+        graph
+            .sorted()
+            .into_iter()
+            .flat_map(|module| {
+                let node = &graph.modules[module];
+
+                graph
+                    .dependencies(module)
+                    .filter(|(target, _)| {
+                        graph.modules[*target].path == self.target
+                    })
+                    .filter_map(|(_, index)| {
+                        node.locate(node.imports[index].span, None)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
@@ -1,25 +1,37 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use clap::{Parser, Subcommand};
-use logger::{ConsoleLogger, JsonLogger, LoggerType, QuickfixLogger};
-use processor::Processor;
+use analysis::dependency_graph::{
+    Diagram, Granularity, GraphFormat, GraphRequest,
+};
+use analysis::query::{Operand, Source};
+use baseline::BaselineLogger;
+use clap::{Args, Parser, Subcommand};
+use config::Config;
+use console::style;
+use edit::EditMode;
+use git::Changes;
+use globset::GlobSet;
+use graph::resolver::Resolver;
+use logger::{
+    ConsoleLogger, JsonLogger, Logger, LoggerType, OutputFormat, QuickfixLogger,
+};
+use pattern::{Pattern, Template};
+use processor::{Processor, ProcessorOptions, ProcessorRequest, Summary};
 
 mod analysis;
+mod baseline;
+mod config;
+mod edit;
+mod git;
+mod graph;
 mod logger;
+mod lsp;
 mod parser;
+mod pattern;
 mod processor;
 mod utils;
-
-#[derive(clap::ValueEnum, Clone, Debug)]
-enum OutputFormat {
-    /// The default console format
-    Default,
-    /// Formats the output as JSON
-    Json,
-    /// Formats the output as a Vim quickfix list
-    #[value(alias("vi"))]
-    Quickfix,
-}
+mod watch;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -28,10 +40,75 @@ struct Cli {
     #[arg(long, default_value = ".")]
     cwd: String,
 
+    /// A file or directory to search, like the search paths given after the
+    /// arguments of the command. Can be repeated
+    #[arg(long = "path", value_name = "PATH", global = true)]
+    paths: Vec<PathBuf>,
+
+    /// Glob matching the files to search, relative to the working directory
+    /// (e.g., src/**). Overrides the config file. Can be repeated
+    #[arg(long)]
+    include: Vec<String>,
+
+    /// Glob matching files to skip, relative to the working directory (e.g.,
+    /// **/*.test.tsx). Added to the config file. Can be repeated
+    #[arg(long)]
+    exclude: Vec<String>,
+
+    /// Search files ignored by .gitignore and other ignore files
+    #[arg(long)]
+    no_ignore: bool,
+
+    /// Only report matches in files added or modified since the merge base of
+    /// this git ref (e.g., origin/main), including uncommitted changes
+    #[arg(long, value_name = "REF")]
+    changed_since: Option<String>,
+
+    /// Only report matches in files with staged changes
+    #[arg(long, conflicts_with = "changed_since")]
+    staged: bool,
+
     /// The output format. The default format is a human-readable console output.
     #[arg(long)]
     format: Option<OutputFormat>,
 
+    /// The config file to use. By default, flashlight.toml is searched for in
+    /// the working directory and its parent directories.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Compare matches to a baseline file and only report new matches. The
+    /// baseline is created from the matches if it doesn't exist.
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+
+    /// Exit with a failure status when matches are found instead of when no
+    /// matches are found
+    #[arg(long)]
+    fail_on_match: bool,
+
+    /// Update the baseline when matches have been removed
+    #[arg(long, requires = "baseline")]
+    update_baseline: bool,
+
+    /// Keep running and print the matches again when files change
+    #[arg(long, conflicts_with = "baseline")]
+    watch: bool,
+
+    /// Print a unified diff of the changes instead of editing files, for
+    /// commands which edit files
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Exit with a failure status if files would be edited, without editing
+    /// them. Only commands which edit files can be checked.
+    #[arg(long)]
+    check: bool,
+
+    /// Prompt before applying each change when editing files
+    #[arg(long, short, conflicts_with_all = ["dry_run", "check", "watch"])]
+    interactive: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -41,56 +118,965 @@ enum Commands {
     /// Find imports
     Imports {
         /// The import source (e.g., react)
-        #[arg(index = 1)]
         source: String,
 
         /// Only include imports containing this import specifier (e.g., useState)
-        #[arg(index = 2)]
         specifier: Option<String>,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Find member expressions
+    Members {
+        /// The dotted member path, where `*` matches any property (e.g.,
+        /// process.env.*, window.location)
+        pattern: String,
+
+        /// Only report each distinct member name once
+        #[arg(long)]
+        unique: bool,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Find references to an imported binding
+    References {
+        /// The import source (e.g., react)
+        source: String,
+
+        /// The imported name (e.g., useState). Use `default` for default
+        /// imports or `*` for namespace imports
+        specifier: String,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Check imports against the boundaries and rules in the config file
+    Check {
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Find circular dependencies
+    Cycles {
+        /// Ignore type only imports since they are erased at runtime
+        #[arg(long)]
+        ignore_type_imports: bool,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Print the dependency graph of the project
+    Graph {
+        /// The diagram language of the graph. The graph is printed as JSON
+        /// instead with `--format json`
+        #[arg(long, value_enum, default_value = "dot")]
+        diagram: Diagram,
+
+        /// Collapse modules into their directory or package
+        #[arg(long, value_enum, default_value = "file")]
+        collapse: Granularity,
+
+        /// Only include modules imported by this module, directly or
+        /// indirectly (e.g., src/index.ts)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// The maximum number of imports to follow from the root module
+        #[arg(long, requires = "root")]
+        depth: Option<usize>,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Find unused, missing and misplaced package dependencies
+    Deps {
+        /// Glob matching modules which are allowed to import dev dependencies
+        /// (e.g., **/*.test.ts). Can be repeated
+        #[arg(long = "dev")]
+        dev_files: Vec<String>,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// List exports
+    Exports {
+        #[command(flatten)]
+        roots: Roots,
     },
     /// Find JSX tags
     Tags {
         /// The name of the tag (e.g., div)
-        #[arg(index = 1)]
         name: String,
 
         /// Only include tags with this attribute/value (e.g., onClick, type="button")
-        #[arg(index = 2)]
         attribute: Option<String>,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Find function and method calls
+    Calls {
+        /// The name of the function or method (e.g., useEffect, analytics.track)
+        name: String,
+
+        /// Only include calls with these literal argument values, matched by
+        /// position. Use `_` to match any argument (e.g., Signup). Trailing
+        /// values naming existing files or directories are search paths
+        arguments: Vec<String>,
+
+        /// Only include calls with exactly this number of arguments
+        #[arg(long)]
+        arg_count: Option<usize>,
+
+        /// Only include calls to bindings imported from this source (e.g., react)
+        #[arg(long)]
+        source: Option<String>,
+    },
+    /// Find exports which are never imported by another module
+    UnusedExports {
+        /// Glob matching entry point modules whose exports are public (e.g.,
+        /// src/index.ts). Can be repeated
+        #[arg(long = "entry")]
+        entries: Vec<String>,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Find code matching a pattern, ignoring formatting
+    Search {
+        /// The code pattern, where `$NAME` matches any node and `$$$NAME`
+        /// matches any number of arguments or other list items (e.g.,
+        /// 'useEffect($CB, [])', '<Button variant=$V />')
+        pattern: String,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Replace code matching a pattern
+    Replace {
+        /// The code pattern, where `$NAME` matches any node and `$$$NAME`
+        /// matches any number of arguments or other list items (e.g.,
+        /// '$A.map($F).filter(Boolean)')
+        pattern: String,
+
+        /// The replacement, where metavariables from the pattern are replaced
+        /// with the code they matched (e.g., '$A.flatMap($F)')
+        template: String,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Rewrite imports from one source to another, optionally moving and
+    /// renaming individual specifiers
+    RewriteImports {
+        /// The current import source (e.g., @acme/old-ui)
+        from: String,
+
+        /// The new import source (e.g., @acme/ui)
+        to: String,
+
+        /// Only move this imported name, splitting it from the rest of the
+        /// import. Use `default` for default imports or `*` for namespace
+        /// imports. Can be repeated
+        #[arg(long = "specifier")]
+        specifiers: Vec<String>,
+
+        /// Rename an imported name (e.g., Button=BaseButton). Can be repeated
+        #[arg(long = "rename")]
+        renames: Vec<String>,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Rewrite JSX tags and their attributes
+    RewriteTags {
+        /// The name of the tag (e.g., Button)
+        name: String,
+
+        /// Only rewrite tags with this attribute/value (e.g., variant=primary)
+        attribute: Option<String>,
+
+        /// Rename the tag, updating its import (e.g., BaseButton)
+        #[arg(long)]
+        rename: Option<String>,
+
+        /// Rename an attribute (e.g., color=tone). Can be repeated
+        #[arg(long = "rename-attribute")]
+        rename_attributes: Vec<String>,
+
+        /// Set an attribute, adding it when missing (e.g., variant=solid).
+        /// Values wrapped in braces are expressions and attributes without a
+        /// value are boolean attributes. Can be repeated
+        #[arg(long = "set")]
+        set: Vec<String>,
+
+        /// Remove an attribute. Can be repeated
+        #[arg(long = "remove")]
+        remove: Vec<String>,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Find files matching a combination of queries
+    Query {
+        /// The queries to combine with `and`, `or`, `not` and parentheses.
+        /// Commands are wrapped in brackets while other names are saved
+        /// queries (e.g., '[imports react-query] and not [tags QueryBoundary]')
+        query: String,
+
+        /// Print the matches of this query, numbered from 1 in the order they
+        /// appear, instead of the matching files
+        #[arg(long, value_name = "N")]
+        matches: Option<usize>,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Run the queries in a file, parsing each file only once
+    Batch {
+        /// The TOML file of queries, each being the arguments of a command
+        /// keyed by its name (e.g., moment = ["imports", "moment"])
+        file: PathBuf,
+
+        #[command(flatten)]
+        roots: Roots,
     },
+    /// Run a query saved in the config file
+    Run {
+        /// The name of the query
+        name: String,
+
+        #[command(flatten)]
+        roots: Roots,
+    },
+    /// Start a language server over stdio which reports the matches of the
+    /// check command and saved queries as diagnostics
+    Lsp,
     /// Find unused modules
     UnusedModules,
 }
 
+/// The files and directories searched by a command
+#[derive(Args, Default)]
+struct Roots {
+    /// The files and directories to search, relative to the working
+    /// directory. Defaults to the working directory
+    #[arg(id = "roots", value_name = "PATH")]
+    paths: Vec<PathBuf>,
+}
+
+impl Commands {
+    /// Take the search paths given after the arguments of the command. Like
+    /// the revisions and paths of git, the optional argument of `imports`,
+    /// `tags` and `rewrite-tags` and the trailing arguments of `calls` are
+    /// search paths when they name existing files or directories.
+    fn take_roots(&mut self, cwd: &Path) -> Vec<PathBuf> {
+        let is_path = |arg: &String| cwd.join(arg).exists();
+        let mut paths = Vec::new();
+
+        match self {
+            Commands::Imports { specifier: optional, .. }
+            | Commands::Tags { attribute: optional, .. }
+            | Commands::RewriteTags { attribute: optional, .. } => {
+                if let Some(arg) = optional.take_if(|arg| is_path(arg)) {
+                    paths.push(PathBuf::from(arg));
+                }
+            }
+            Commands::Calls { arguments, .. } => {
+                let literals = arguments
+                    .iter()
+                    .rposition(|arg| !is_path(arg))
+                    .map_or(0, |index| index + 1);
+
+                paths.extend(
+                    arguments
+                        .split_off(literals)
+                        .into_iter()
+                        .map(PathBuf::from),
+                );
+            }
+            _ => {}
+        }
+
+        if let Some(roots) = self.roots_mut() {
+            paths.append(&mut roots.paths);
+        }
+
+        paths
+    }
+
+    /// Whether the command edits files rather than only reporting matches
+    fn edits_files(&self) -> bool {
+        matches!(
+            self,
+            Commands::Replace { .. }
+                | Commands::RewriteImports { .. }
+                | Commands::RewriteTags { .. }
+        )
+    }
+
+    fn roots_mut(&mut self) -> Option<&mut Roots> {
+        match self {
+            Commands::Imports { roots, .. }
+            | Commands::Members { roots, .. }
+            | Commands::References { roots, .. }
+            | Commands::Check { roots }
+            | Commands::Cycles { roots, .. }
+            | Commands::Graph { roots, .. }
+            | Commands::Deps { roots, .. }
+            | Commands::Exports { roots }
+            | Commands::Tags { roots, .. }
+            | Commands::UnusedExports { roots, .. }
+            | Commands::Search { roots, .. }
+            | Commands::Replace { roots, .. }
+            | Commands::RewriteImports { roots, .. }
+            | Commands::RewriteTags { roots, .. }
+            | Commands::Query { roots, .. }
+            | Commands::Batch { roots, .. }
+            | Commands::Run { roots, .. } => Some(roots),
+            Commands::Calls { .. }
+            | Commands::Lsp
+            | Commands::UnusedModules => None,
+        }
+    }
+}
+
+/// A command saved as a query in the config file
+#[derive(Parser)]
+struct Query {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// Settings shared by all commands from the config file and CLI flags
+struct Settings {
+    config: Option<Config>,
+    options: ProcessorOptions,
+    resolver: Resolver,
+    format: OutputFormat,
+    fail_on_match: bool,
+    watch: bool,
+}
+
+impl Settings {
+    fn load(cli: &Cli) -> Settings {
+        let config_path = match &cli.config {
+            Some(path) => Some(path.clone()),
+            None => config::find(Path::new(&cli.cwd)),
+        };
+
+        let config = config_path.map(|path| match config::load(&path) {
+            Ok(config) => config,
+            Err(err) => exit_with_error(format!(
+                "Failed to load {}: {}",
+                path.display(),
+                err
+            )),
+        });
+
+        let resolver = match &config {
+            Some(Config { tsconfig: Some(tsconfig), dir, .. }) => {
+                let path = dir.join(tsconfig);
+
+                Resolver::from_tsconfig(&path).unwrap_or_else(|err| {
+                    exit_with_error(format!(
+                        "Failed to load {}: {}",
+                        path.display(),
+                        err
+                    ))
+                })
+            }
+            _ => Resolver::default(),
+        };
+
+        // Globs from the config file are relative to it, while those passed
+        // as flags are relative to the working directory.
+        let cwd = Path::new(&cli.cwd);
+        let (mut include, mut exclude) = match &config {
+            Some(config) => (
+                utils::absolute_globs(&config.dir, &config.include),
+                utils::absolute_globs(&config.dir, &config.exclude),
+            ),
+            None => (vec![], vec![]),
+        };
+
+        if !cli.include.is_empty() {
+            include = utils::absolute_globs(cwd, &cli.include);
+        }
+
+        exclude.extend(utils::absolute_globs(cwd, &cli.exclude));
+
+        let options = ProcessorOptions {
+            paths: cli.paths.iter().map(|path| cwd.join(path)).collect(),
+            include: build_glob_set(&include)
+                .unwrap_or_else(|err| exit_with_error(err)),
+            exclude: build_glob_set(&exclude)
+                .unwrap_or_else(|err| exit_with_error(err)),
+            extensions: config
+                .as_ref()
+                .map_or_else(Vec::new, |config| config.extensions.clone()),
+            parser: config
+                .as_ref()
+                .map_or_else(Default::default, |config| config.parser),
+            no_ignore: cli.no_ignore,
+            changed: get_changed_files(cli),
+            edit_mode: match (cli.dry_run, cli.check, cli.interactive) {
+                (true, _, _) => EditMode::DryRun,
+                (_, true, _) => EditMode::Check,
+                (_, _, true) => EditMode::Interactive,
+                _ => EditMode::Write,
+            },
+        };
+
+        let format = cli
+            .format
+            .or(config.as_ref().and_then(|config| config.format))
+            .unwrap_or(OutputFormat::Default);
+
+        Settings {
+            config,
+            options,
+            resolver,
+            format,
+            fail_on_match: cli.fail_on_match,
+            watch: cli.watch,
+        }
+    }
+}
+
 fn main() {
-    let cli = Cli::parse();
-    let mut logger = get_logger(cli.format.unwrap_or(OutputFormat::Default));
+    let mut cli = Cli::parse();
+    let roots = cli.command.take_roots(Path::new(&cli.cwd));
+    cli.paths.extend(roots);
+
+    // The language server talks over stdout, so it can't print anything else
+    // or run forever
+    if matches!(cli.command, Commands::Lsp)
+        && (cli.watch || cli.dry_run || cli.baseline.is_some())
+    {
+        exit_with_error(
+            "The language server can't be run with --watch, --dry-run or \
+             --baseline",
+        );
+    }
+
+    let mut settings = Settings::load(&cli);
+    let mut logger = get_logger(settings.format);
+
+    let code = match cli.baseline {
+        Some(baseline) => {
+            let mut logger = BaselineLogger::new(logger, Path::new(&cli.cwd));
+
+            // New matches always fail when comparing to a baseline
+            settings.fail_on_match = true;
+            let code = run(cli.command, cli.cwd, &mut logger, &settings);
+
+            match logger.finish(&baseline, cli.update_baseline) {
+                Ok(_) if code == 2 => 2,
+                Ok(0) => 0,
+                Ok(_) => 1,
+                Err(err) => exit_with_error(format!(
+                    "Failed to load baseline {}: {}",
+                    baseline.display(),
+                    err
+                )),
+            }
+        }
+        None => run(cli.command, cli.cwd, &mut logger, &settings),
+    };
+
+    std::process::exit(code);
+}
+
+/// Run a command and get its exit code
+fn run<L: Logger>(
+    command: Commands,
+    cwd: String,
+    logger: &mut L,
+    settings: &Settings,
+) -> i32 {
+    match command {
+        Commands::Batch { file, .. } => {
+            let queries = match config::load_queries(&file) {
+                Ok(queries) => queries,
+                Err(err) => exit_with_error(format!(
+                    "Failed to load {}: {}",
+                    file.display(),
+                    err
+                )),
+            };
+
+            if queries.is_empty() {
+                exit_with_error(format!("No queries in {}", file.display()));
+            }
+
+            if settings.watch {
+                exit_with_error(
+                    "Batches can't be run in watch mode".to_string(),
+                );
+            }
+
+            if matches!(settings.options.edit_mode, EditMode::Check) {
+                exit_with_error(
+                    "--check can only be used with commands which edit files",
+                );
+            }
+
+            let requests = queries
+                .into_iter()
+                .map(|(name, args)| {
+                    let operand = parse_query(&args)
+                        .map_err(|err| {
+                            format!("Invalid query {}: {}", name, err)
+                        })
+                        .and_then(|command| {
+                            build_operand(command, &cwd, settings, &mut vec![])
+                        })
+                        .unwrap_or_else(|err| exit_with_error(err));
+
+                    (name, operand)
+                })
+                .collect();
+
+            get_exit_code(
+                Processor::batch(requests, logger, &settings.options).process(),
+                settings.fail_on_match,
+            )
+        }
+        Commands::Run { name, .. } => {
+            let command = resolve_query(&name, settings)
+                .unwrap_or_else(|err| exit_with_error(err));
+
+            if let Commands::Run { .. } | Commands::Lsp = command {
+                exit_with_error(format!(
+                    "Query {} can't run another query or the language server",
+                    name
+                ));
+            }
 
-    match cli.command {
-        Commands::Imports { source, specifier } => {
-            let request = analysis::imports::ImportsRequest {
-                path: PathBuf::from(cli.cwd),
+            run(command, cwd, logger, settings)
+        }
+        Commands::Lsp => lsp::serve(cwd, settings),
+        Commands::UnusedModules => {
+            todo!();
+        }
+        command => {
+            // Checking fails when files would be edited, so it only applies
+            // to commands which edit files
+            let is_checking =
+                matches!(settings.options.edit_mode, EditMode::Check);
+            if is_checking && !command.edits_files() {
+                exit_with_error(
+                    "--check can only be used with commands which edit files",
+                );
+            }
+
+            let is_check = matches!(command, Commands::Check { .. });
+            let is_graph = matches!(command, Commands::Graph { .. });
+            let request = build_request(command, &cwd, settings, &mut vec![])
+                .unwrap_or_else(|err| exit_with_error(err));
+            let summary = execute(request, logger, settings);
+
+            if is_check {
+                // Violations are failures rather than results, so the check
+                // always fails when errors are found. Warnings don't count.
+                get_exit_code(
+                    Summary { matches: summary.failures, ..summary },
+                    true,
+                )
+            } else if is_graph {
+                // The graph is printed as a single document rather than
+                // matches, so only errors affect the exit code.
+                match summary.errors {
+                    0 => 0,
+                    _ => 2,
+                }
+            } else {
+                get_exit_code(summary, settings.fail_on_match || is_checking)
+            }
+        }
+    }
+}
+
+/// Build the request run by a command. Errors are returned rather than
+/// exiting so the language server can report them and keep running. Saved
+/// queries combined in a query are resolved, with `names` tracking those being
+/// resolved to detect cycles.
+fn build_request(
+    command: Commands,
+    cwd: &str,
+    settings: &Settings,
+    names: &mut Vec<String>,
+) -> Result<Box<dyn Operand>, String> {
+    let path = PathBuf::from(cwd);
+
+    Ok(match command {
+        Commands::Imports { source, specifier, .. } => {
+            Box::new(analysis::imports::ImportsRequest {
+                path,
                 source,
                 specifier,
+            })
+        }
+        Commands::Members { pattern, unique, .. } => Box::new(
+            analysis::members::MembersRequest::new(path, pattern, unique),
+        ),
+        Commands::References { source, specifier, .. } => {
+            Box::new(analysis::references::ReferencesRequest {
+                path,
+                source,
+                specifier,
+            })
+        }
+        Commands::Check { .. } => {
+            let config = settings.config.as_ref().ok_or_else(|| {
+                format!(
+                    "No {} found in {} or its parent directories",
+                    config::CONFIG_FILE,
+                    cwd
+                )
+            })?;
+
+            Box::new(
+                analysis::check::CheckRequest::new(
+                    path,
+                    config,
+                    config.dir.clone(),
+                    settings.resolver.clone(),
+                )
+                .map_err(|err| err.to_string())?,
+            )
+        }
+        Commands::Cycles { ignore_type_imports, .. } => {
+            Box::new(analysis::cycles::CyclesRequest::new(
+                path,
+                ignore_type_imports,
+                settings.resolver.clone(),
+            ))
+        }
+        Commands::Graph { diagram, collapse, root, depth, .. } => {
+            let format = match settings.format {
+                OutputFormat::Json => GraphFormat::Json,
+                _ => GraphFormat::Diagram(diagram),
             };
 
-            Processor::new(request, &mut logger).process();
+            Box::new(GraphRequest::new(
+                path,
+                format,
+                collapse,
+                root,
+                depth,
+                settings.options.changed.clone(),
+                settings.resolver.clone(),
+            ))
+        }
+        Commands::Deps { dev_files, .. } => {
+            Box::new(analysis::deps::DepsRequest::new(
+                path,
+                build_glob_set(&dev_files)?,
+                utils::absolutize(Path::new(cwd)),
+                settings.resolver.clone(),
+            ))
+        }
+        Commands::Exports { .. } => {
+            Box::new(analysis::exports::ExportsRequest { path })
         }
-        Commands::Tags { name, attribute } => {
+        Commands::Tags { name, attribute, .. } => {
             let pair = utils::parse_key_value(attribute);
-            let request = analysis::tags::TagsRequest {
-                path: PathBuf::from(cli.cwd),
+
+            Box::new(analysis::tags::TagsRequest {
+                path,
                 name,
                 attribute: pair.0,
                 value: pair.1,
+            })
+        }
+        Commands::Calls { name, arguments, arg_count, source } => {
+            Box::new(analysis::calls::CallsRequest {
+                path,
+                name,
+                arguments,
+                arg_count,
+                source,
+            })
+        }
+        Commands::UnusedExports { entries, .. } => {
+            // Entry points passed as flags are relative to the working
+            // directory, while those in the config file are relative to it.
+            let (entries, root) = match &settings.config {
+                Some(config) if entries.is_empty() => {
+                    (config.entries.clone(), config.dir.clone())
+                }
+                _ => (entries, utils::absolutize(Path::new(cwd))),
             };
 
-            Processor::new(request, &mut logger).process();
+            Box::new(analysis::unused_exports::UnusedExportsRequest::new(
+                path,
+                build_glob_set(&entries)?,
+                root,
+                settings.resolver.clone(),
+            ))
         }
-        Commands::UnusedModules => {
-            todo!();
+        Commands::Search { pattern, .. } => {
+            Box::new(analysis::search::SearchRequest {
+                path,
+                pattern: parse_pattern(&pattern, settings)?,
+            })
+        }
+        Commands::Replace { pattern, template, .. } => {
+            let pattern = parse_pattern(&pattern, settings)?.exact();
+            let template = Template::parse(&template, &pattern)
+                .map_err(|err| format!("Invalid template: {}", err))?;
+
+            Box::new(analysis::replace::ReplaceRequest {
+                path,
+                pattern,
+                template,
+            })
+        }
+        Commands::RewriteImports { from, to, specifiers, renames, .. } => {
+            Box::new(analysis::rewrite_imports::RewriteImportsRequest {
+                path,
+                from,
+                to,
+                specifiers,
+                renames: parse_renames(&renames)?,
+            })
+        }
+        Commands::RewriteTags {
+            name,
+            attribute,
+            rename,
+            rename_attributes,
+            set,
+            remove,
+            ..
+        } => {
+            let pair = utils::parse_key_value(attribute);
+            let set = set
+                .iter()
+                .map(|attr| match attr.split_once('=') {
+                    Some((name, value)) => {
+                        (name.to_string(), Some(value.to_string()))
+                    }
+                    None => (attr.clone(), None),
+                })
+                .collect();
+
+            Box::new(analysis::rewrite_tags::RewriteTagsRequest {
+                path,
+                name,
+                attribute: pair.0,
+                value: pair.1,
+                rename,
+                rename_attributes: parse_renames(&rename_attributes)?,
+                set,
+                remove,
+            })
+        }
+        Commands::Query { query, matches, .. } => {
+            let (expr, sources) = analysis::query::parse(&query)
+                .map_err(|err| format!("Invalid query: {}", err))?;
+
+            let report = match matches {
+                Some(n) if n == 0 || n > sources.len() => {
+                    return Err(format!(
+                        "Invalid --matches {}, the query has {} operands",
+                        n,
+                        sources.len()
+                    ))
+                }
+                Some(n) if !expr.is_positive(n - 1) => {
+                    return Err(format!(
+                        "Operand {} is negated, so it has no matches in the \
+                         matching files",
+                        n
+                    ))
+                }
+                matches => matches.map(|n| n - 1),
+            };
+
+            let operands = sources
+                .into_iter()
+                .map(|source| {
+                    let command = match source {
+                        Source::Command(args) => {
+                            parse_query(&args).map_err(|err| {
+                                format!(
+                                    "Invalid query [{}]: {}",
+                                    args.join(" "),
+                                    err
+                                )
+                            })?
+                        }
+                        Source::Saved(name) => {
+                            Commands::Run { name, roots: Roots::default() }
+                        }
+                    };
+
+                    build_operand(command, cwd, settings, names)
+                })
+                .collect::<Result<_, String>>()?;
+
+            Box::new(analysis::query::QueryRequest {
+                path,
+                expr,
+                operands,
+                report,
+            })
+        }
+        Commands::Batch { .. }
+        | Commands::Run { .. }
+        | Commands::Lsp
+        | Commands::UnusedModules => {
+            return Err(
+                "Batches, saved queries and the language server don't run \
+                 a single request"
+                    .to_string(),
+            )
+        }
+    })
+}
+
+/// Build the request of a command combined in a query or batch. Only commands
+/// which report matches in each module on its own can be combined. Saved
+/// queries are resolved, with `names` tracking those being resolved to detect
+/// cycles.
+fn build_operand(
+    command: Commands,
+    cwd: &str,
+    settings: &Settings,
+    names: &mut Vec<String>,
+) -> Result<Box<dyn Operand>, String> {
+    match command {
+        Commands::Run { name, .. } => {
+            if names.contains(&name) {
+                return Err(format!("Query {} references itself", name));
+            }
+
+            let command = resolve_query(&name, settings)?;
+
+            names.push(name);
+            let operand = build_operand(command, cwd, settings, names);
+            names.pop();
+
+            operand
+        }
+        Commands::Imports { .. }
+        | Commands::Members { .. }
+        | Commands::References { .. }
+        | Commands::Exports { .. }
+        | Commands::Tags { .. }
+        | Commands::Calls { .. }
+        | Commands::Search { .. }
+        | Commands::Query { .. } => {
+            build_request(command, cwd, settings, names)
         }
+        _ => Err("Only commands finding matches in each file on their own, \
+                  such as imports, tags and search, can be combined in a \
+                  query or batch"
+            .to_string()),
+    }
+}
+
+/// Get the command of a query saved in the config file
+fn resolve_query(name: &str, settings: &Settings) -> Result<Commands, String> {
+    let args = settings
+        .config
+        .as_ref()
+        .and_then(|config| config.queries.get(name))
+        .ok_or_else(|| format!("Unknown query {}", name))?;
+
+    parse_query(args).map_err(|err| format!("Invalid query {}: {}", name, err))
+}
+
+/// Parse the arguments of a saved query into a command. Queries are run
+/// against the search paths of the command line, so they can't have their own.
+fn parse_query(args: &[String]) -> Result<Commands, String> {
+    let mut command = Query::try_parse_from(
+        std::iter::once("flashlight").chain(args.iter().map(String::as_str)),
+    )
+    .map_err(|err| err.kind().to_string())?
+    .command;
+
+    match command.roots_mut() {
+        Some(roots) if !roots.paths.is_empty() => {
+            Err("Search paths can only be given on the command line"
+                .to_string())
+        }
+        _ => Ok(command),
+    }
+}
+
+fn parse_pattern(
+    pattern: &str,
+    settings: &Settings,
+) -> Result<Pattern, String> {
+    Pattern::parse(pattern, settings.options.parser)
+        .map_err(|err| format!("Invalid pattern: {}", err))
+}
+
+/// Parse renames formatted as `Old=New` into new names keyed by the old name
+fn parse_renames(
+    renames: &[String],
+) -> Result<HashMap<String, String>, String> {
+    renames
+        .iter()
+        .map(|rename| match rename.split_once('=') {
+            Some((old, new)) => Ok((old.to_string(), new.to_string())),
+            None => Err(format!("Invalid rename {}, expected Old=New", rename)),
+        })
+        .collect()
+}
+
+/// Process a request, or keep processing it as files change in watch mode
+fn execute<L, R>(request: R, logger: &mut L, settings: &Settings) -> Summary
+where
+    L: Logger,
+    R: ProcessorRequest + Clone,
+{
+    if settings.watch {
+        watch::watch(request, &settings.options, settings.format);
+    }
+
+    Processor::new(request, logger, &settings.options).process()
+}
+
+/// Get the exit code of a command like `grep`: 0 when matches are found, 1
+/// when no matches are found, and 2 when files couldn't be processed. When
+/// failing on matches, the codes for matches and no matches are swapped.
+fn get_exit_code(summary: Summary, fail_on_match: bool) -> i32 {
+    if summary.errors > 0 {
+        return 2;
+    }
+
+    match (summary.matches > 0, fail_on_match) {
+        (true, false) | (false, true) => 0,
+        _ => 1,
+    }
+}
+
+/// Get the files changed in git when only searching changed files
+fn get_changed_files(cli: &Cli) -> Option<HashSet<PathBuf>> {
+    let changes = match &cli.changed_since {
+        Some(reference) => Changes::Since(reference.clone()),
+        None if cli.staged => Changes::Staged,
+        None => return None,
     };
+
+    match git::changed_files(Path::new(&cli.cwd), &changes) {
+        Ok(files) => Some(files),
+        Err(err) => exit_with_error(err),
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet, String> {
+    utils::build_glob_set(patterns).map_err(|err| err.to_string())
+}
+
+fn exit_with_error(err: impl std::fmt::Display) -> ! {
+    eprintln!("{}: {}", style("error").red().bold(), err);
+    std::process::exit(2);
 }
 
 fn get_logger(format: OutputFormat) -> LoggerType {
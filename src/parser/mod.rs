@@ -0,0 +1,150 @@
+mod sfc;
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use swc_common::sync::Lrc;
+use swc_common::{BytePos, FileName, SourceFile, SourceMap, Span};
+use swc_ecma_ast::{EsVersion, Module};
+use swc_ecma_parser::lexer::Lexer;
+use swc_ecma_parser::{EsConfig, Parser, StringInput, Syntax, TsConfig};
+
+pub struct ParsedModule {
+    pub path: PathBuf,
+    pub source_map: Lrc<SourceMap>,
+    pub module: Module,
+}
+
+pub enum ParseError {
+    IO(std::io::Error),
+    Parse(swc_ecma_parser::error::Error, Lrc<SourceMap>),
+}
+
+/// Options for parsing modules, which can be set in the config file
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct ParserOptions {
+    /// Parse JSX in JavaScript files. TypeScript files only allow JSX in
+    /// `.tsx` files.
+    pub jsx: bool,
+    pub decorators: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions { jsx: true, decorators: true }
+    }
+}
+
+pub fn parse(
+    path: &Path,
+    options: ParserOptions,
+) -> Result<ParsedModule, ParseError> {
+    let source_map: Lrc<SourceMap> = Default::default();
+    let file = source_map.load_file(path).map_err(ParseError::IO)?;
+    let ext = path.extension().map_or("", |ext| ext.to_str().unwrap_or(""));
+
+    let module = match sfc::is_component(ext) {
+        true => parse_component(&file, ext, options),
+        false => Parser::new_from(Lexer::new(
+            guess_syntax(path, options),
+            EsVersion::EsNext,
+            StringInput::from(&*file),
+            None,
+        ))
+        .parse_module(),
+    };
+
+    match module {
+        Ok(module) => {
+            Ok(ParsedModule { path: path.to_path_buf(), module, source_map })
+        }
+        Err(err) => Err(ParseError::Parse(err, source_map)),
+    }
+}
+
+/// Parse the scripts of a single-file component into a single module. Each
+/// script is parsed in place, so locations are relative to the component
+/// rather than the script. Components without scripts are empty modules.
+fn parse_component(
+    file: &SourceFile,
+    ext: &str,
+    options: ParserOptions,
+) -> Result<Module, swc_ecma_parser::error::Error> {
+    let mut module = Module {
+        span: Span::new(file.start_pos, file.start_pos, Default::default()),
+        body: vec![],
+        shebang: None,
+    };
+
+    for script in sfc::scripts(ext, &file.src) {
+        let lo = file.start_pos + BytePos(script.range.start as u32);
+        let hi = file.start_pos + BytePos(script.range.end as u32);
+        let path = format!("script.{}", script.lang.as_deref().unwrap_or("js"));
+
+        let lexer = Lexer::new(
+            guess_syntax(Path::new(&path), options),
+            EsVersion::EsNext,
+            StringInput::new(&file.src[script.range], lo, hi),
+            None,
+        );
+
+        let script = Parser::new_from(lexer).parse_module()?;
+
+        if module.body.is_empty() {
+            module.span = script.span;
+        }
+
+        module.span.hi = script.span.hi;
+        module.body.extend(script.body);
+    }
+
+    Ok(module)
+}
+
+/// Parse the source of a code pattern as a module. Patterns use the
+/// TypeScript syntax so they can contain types, and JSX unless it is disabled.
+pub fn parse_pattern(
+    source: &str,
+    options: ParserOptions,
+) -> Result<Module, String> {
+    let source_map: Lrc<SourceMap> = Default::default();
+    let file = source_map.new_source_file(FileName::Anon, source.to_string());
+    let path =
+        Path::new(if options.jsx { "pattern.tsx" } else { "pattern.ts" });
+
+    let lexer = Lexer::new(
+        guess_syntax(path, options),
+        EsVersion::EsNext,
+        StringInput::from(&*file),
+        None,
+    );
+
+    Parser::new_from(lexer)
+        .parse_module()
+        .map_err(|err| err.kind().msg().to_string())
+}
+
+/// Guess the syntax of the file based on the file extension
+fn guess_syntax(path: &Path, options: ParserOptions) -> Syntax {
+    let ext = path.extension().map_or("", |ext| ext.to_str().unwrap_or(""));
+
+    match ext {
+        "ts" | "cts" | "mts" => Syntax::Typescript(TsConfig {
+            decorators: options.decorators,
+            ..Default::default()
+        }),
+        "tsx" => Syntax::Typescript(TsConfig {
+            decorators: options.decorators,
+            tsx: true,
+            ..Default::default()
+        }),
+        &_ => Syntax::Es(EsConfig {
+            decorators: options.decorators,
+            decorators_before_export: true,
+            import_attributes: true,
+            jsx: options.jsx,
+            ..Default::default()
+        }),
+    }
+}
@@ -0,0 +1,156 @@
+//! Extracts the scripts of single-file components. Vue and Svelte components
+//! contain `<script>` blocks, while Astro components have a frontmatter script
+//! fenced by `---` in addition to `<script>` blocks. Only the offsets of the
+//! scripts are extracted so they can be parsed in place, keeping locations
+//! relative to the component file.
+
+use std::ops::Range;
+
+/// A script in a component, with the language set by its `lang` attribute
+pub struct Script {
+    pub range: Range<usize>,
+    pub lang: Option<String>,
+}
+
+/// Check if a file extension is for a single-file component
+pub fn is_component(ext: &str) -> bool {
+    matches!(ext, "vue" | "svelte" | "astro")
+}
+
+/// Find the scripts of a component in the order they appear
+pub fn scripts(ext: &str, src: &str) -> Vec<Script> {
+    let mut scripts = Vec::new();
+    let mut pos = 0;
+
+    // Astro frontmatter is always TypeScript
+    if ext == "astro" {
+        if let Some(range) = frontmatter(src) {
+            pos = range.end;
+            scripts.push(Script { range, lang: Some("ts".to_string()) });
+        }
+    }
+
+    while let Some(index) = src[pos..].find('<') {
+        pos += index;
+        let rest = &src[pos..];
+
+        if rest.starts_with("<!--") {
+            pos = rest.find("-->").map_or(src.len(), |end| pos + end + 3);
+            continue;
+        }
+
+        if !is_tag(rest, "script") {
+            pos += 1;
+            continue;
+        }
+
+        let start = match find_tag_end(rest) {
+            Some(end) => pos + end + 1,
+            None => break,
+        };
+
+        let attributes = &src[pos + "<script".len()..start - 1];
+        let end = find_closing_tag(src, start).unwrap_or(src.len());
+
+        scripts.push(Script {
+            range: start..end,
+            lang: get_attribute(attributes, "lang"),
+        });
+
+        pos = end;
+    }
+
+    scripts
+}
+
+/// Get the range of the frontmatter between the `---` fences at the start of
+/// a file
+fn frontmatter(src: &str) -> Option<Range<usize>> {
+    let trimmed = src.trim_start();
+    let start = src.len() - trimmed.len() + 3;
+
+    if !trimmed.starts_with("---") {
+        return None;
+    }
+
+    let end = src[start..]
+        .match_indices("---")
+        .map(|(index, _)| start + index)
+        .find(|index| src[..*index].ends_with('\n'))?;
+
+    Some(start..end)
+}
+
+/// Check if text starts with an opening tag, ignoring the case of its name
+fn is_tag(text: &str, name: &str) -> bool {
+    let len = name.len() + 1;
+
+    text.len() > len
+        && text.is_char_boundary(len)
+        && text[1..len].eq_ignore_ascii_case(name)
+        && text[len..].starts_with(|c: char| c.is_whitespace() || c == '>')
+}
+
+/// Find the `>` ending an opening tag. Quoted attribute values can contain
+/// `>`, such as the `generic` attribute of Vue scripts.
+fn find_tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (index, c) in tag.char_indices() {
+        match (c, quote) {
+            (c, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('"' | '\'', None) => quote = Some(c),
+            ('>', None) => return Some(index),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn find_closing_tag(src: &str, start: usize) -> Option<usize> {
+    src[start..].match_indices("</").map(|(index, _)| start + index).find(
+        |index| {
+            src.get(index + 2..index + 8)
+                .is_some_and(|name| name.eq_ignore_ascii_case("script"))
+        },
+    )
+}
+
+/// Get the value of an attribute of a tag, which can be unquoted
+fn get_attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+
+    loop {
+        rest = rest.trim_start();
+        let len = rest.find(|c: char| c == '=' || c.is_whitespace())?;
+        let (key, after) = rest.split_at(len);
+        let after = after.trim_start();
+
+        // Boolean attributes such as `setup` don't have a value
+        let Some(value) = after.strip_prefix('=') else {
+            rest = after;
+            continue;
+        };
+
+        let value = value.trim_start();
+        let (value, next) = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = value[1..].find(quote)? + 1;
+                (&value[1..end], &value[end + 1..])
+            }
+            _ => {
+                let end =
+                    value.find(char::is_whitespace).unwrap_or(value.len());
+                (&value[..end], &value[end..])
+            }
+        };
+
+        if key.eq_ignore_ascii_case(name) {
+            return Some(value.to_string());
+        }
+
+        rest = next;
+    }
+}
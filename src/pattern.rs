@@ -0,0 +1,1093 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::mem::{discriminant, Discriminant};
+
+use serde_json::{Map, Value};
+use swc_common::{BytePos, SourceFile, Span, Spanned, DUMMY_SP};
+use swc_ecma_ast::{
+    Decl, Expr, Ident, JSXElement, Module, ModuleDecl, ModuleItem, Stmt,
+    VarDecl,
+};
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::edit;
+use crate::parser::{self, ParserOptions};
+
+/// Keys of AST nodes which don't affect their structure
+const IGNORED_KEYS: [&str; 2] = ["span", "raw"];
+
+/// Types of expressions which need parentheses when pasted next to other code,
+/// such as an operator or a member access
+const COMPOUND_TYPES: [&str; 16] = [
+    "ArrowFunctionExpression",
+    "AssignmentExpression",
+    "AwaitExpression",
+    "BinaryExpression",
+    "ClassExpression",
+    "ConditionalExpression",
+    "FunctionExpression",
+    "NewExpression",
+    "NumericLiteral",
+    "SequenceExpression",
+    "TsAsExpression",
+    "TsSatisfiesExpression",
+    "TsTypeAssertion",
+    "UnaryExpression",
+    "UpdateExpression",
+    "YieldExpression",
+];
+
+/// Keys of optional type information, which match anything when omitted from
+/// the pattern
+const TYPE_KEYS: [&str; 3] =
+    ["typeAnnotation", "typeArguments", "typeParameters"];
+
+/// A code pattern matched structurally against modules, ignoring formatting.
+/// Identifiers written as `$NAME` are metavariables which match any node, and
+/// `$$$NAME` matches any number of items in a list, such as arguments. The
+/// `$_` metavariable matches without binding a value.
+#[derive(Clone)]
+pub struct Pattern {
+    /// The AST of the pattern, serialized so it can be compared to any type
+    /// of node
+    root: Value,
+    /// The kind of nodes which can match the pattern
+    kind: Kind,
+    /// The names of the metavariables in the order they appear
+    names: Vec<String>,
+    /// Whether JSX elements must have the same attributes and children as
+    /// the pattern
+    exact: bool,
+}
+
+/// The kind of typed nodes which can match a pattern, so only those nodes are
+/// compared with it. Kinds can be broader than the type of the pattern, such
+/// as any literal for a string literal.
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Expr(Discriminant<Expr>),
+    /// Identifiers are also names which aren't expressions, such as
+    /// properties, bindings and JSX tag names
+    Ident,
+    /// JSX elements are also children and attribute values
+    JSXElement,
+    Stmt(Discriminant<Stmt>),
+    /// Variable declarations are also the head of loops
+    VarDecl,
+    /// Declarations are also exported
+    Decl(Discriminant<Decl>),
+    ModuleDecl(Discriminant<ModuleDecl>),
+    /// A bare metavariable matches any expression
+    AnyExpr,
+}
+
+/// The code bound to a metavariable
+#[derive(Clone)]
+pub enum Binding {
+    /// A node, and whether it is a compound expression which needs
+    /// parentheses when pasted next to other code
+    Node { span: Option<Span>, compound: bool },
+    /// A list of nodes, spanning from the first node to the last
+    Nodes(Option<Span>),
+    /// JSX attributes, which aren't necessarily next to each other since
+    /// attributes are matched in any order
+    Attributes(Vec<Span>),
+}
+
+/// The nodes bound to a metavariable while matching, borrowed from the
+/// serialized module so that nodes aren't copied for each candidate
+#[derive(Clone)]
+enum Capture<'a> {
+    Node(&'a Value),
+    Nodes(&'a [Value]),
+    Attributes(Vec<&'a Value>),
+}
+
+/// A node matching a pattern and the values bound to its metavariables, in
+/// the order they appear in the pattern
+pub struct PatternMatch {
+    pub span: Span,
+    pub bindings: Vec<(String, Binding)>,
+}
+
+impl Pattern {
+    /// Parse a pattern containing a single expression or statement
+    pub fn parse(
+        source: &str,
+        options: ParserOptions,
+    ) -> Result<Pattern, String> {
+        // JSX attribute values can't be identifiers, so `name=$VALUE` is
+        // parsed as `name={$VALUE}`.
+        let mut wrapped = Vec::new();
+        let module = parser::parse_pattern(source, options).or_else(|err| {
+            let (source, names) = wrap_attribute_values(source);
+            wrapped = names;
+
+            parser::parse_pattern(&source, options).map_err(|_| err)
+        })?;
+
+        let (root, kind) = match module.body.as_slice() {
+            [item] => (
+                serde_json::to_value(item).map_err(|err| err.to_string())?,
+                get_kind(item),
+            ),
+            _ => {
+                return Err(String::from(
+                    "Expected a single expression or statement",
+                ))
+            }
+        };
+
+        // Expressions are parsed as expression statements
+        let mut root = match get_type(&root) {
+            Some("ExpressionStatement") => root["expression"].clone(),
+            _ => root,
+        };
+
+        unwrap_attribute_values(&mut root, &wrapped);
+
+        let mut metavariables = Vec::new();
+        find_metavariables(&root, &mut metavariables);
+        metavariables.sort_by_key(|(pos, _)| *pos);
+
+        let mut names: Vec<String> = Vec::new();
+        for (_, name) in metavariables {
+            if name != "_" && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        let root = unwrap_parens(&root).clone();
+        let kind = match get_metavariable(&root) {
+            Some(_) => Kind::AnyExpr,
+            None => kind,
+        };
+
+        Ok(Pattern { root, kind, names, exact: false })
+    }
+
+    /// Only match JSX elements with the same attributes and children as the
+    /// pattern, so replacing a match doesn't drop code which wasn't matched.
+    /// Attributes can still be in any order.
+    pub fn exact(self) -> Pattern {
+        Pattern { exact: true, ..self }
+    }
+
+    /// Find the nodes of a module matching the pattern. The typed nodes of
+    /// the kind of the pattern are the candidates, which are found by their
+    /// span in the module once it is serialized.
+    pub fn find(&self, module: &Module) -> Vec<PatternMatch> {
+        let mut finder = Finder { pattern: self, candidates: HashSet::new() };
+        module.visit_with(&mut finder);
+
+        let Ok(node) = serde_json::to_value(module) else {
+            return vec![];
+        };
+
+        let mut matches = Vec::new();
+        self.check(&node, &mut finder.candidates, &mut matches);
+
+        // Serialized nodes are visited in the order of their keys, so matches
+        // are sorted in the order of the source with outer matches first
+        matches.sort_by_key(|m| (m.span.lo, Reverse(m.span.hi)));
+        matches
+    }
+
+    /// Match the candidates within a serialized node. Each candidate is only
+    /// compared once, since nodes wrapping it can have the same span.
+    fn check(
+        &self,
+        node: &Value,
+        candidates: &mut HashSet<(BytePos, BytePos)>,
+        matches: &mut Vec<PatternMatch>,
+    ) {
+        let object = match node {
+            Value::Object(object) => object,
+            Value::Array(items) => {
+                for item in items {
+                    self.check(item, candidates, matches);
+                }
+
+                return;
+            }
+            _ => return,
+        };
+
+        if let Some(span) = get_span(node).filter(|_| self.is_candidate(node)) {
+            let mut bindings = Vec::new();
+
+            if candidates.remove(&(span.lo, span.hi))
+                && self.match_node(&self.root, node, &mut bindings)
+            {
+                bindings.sort_by_key(|(name, _)| {
+                    self.names.iter().position(|n| n == name)
+                });
+
+                matches.push(PatternMatch {
+                    span,
+                    bindings: bindings
+                        .iter()
+                        .map(|(name, capture)| {
+                            (name.clone(), capture.binding())
+                        })
+                        .collect(),
+                });
+            }
+        }
+
+        for value in object.values() {
+            self.check(value, candidates, matches);
+        }
+    }
+
+    /// Check if a serialized node can be a candidate. Arguments take the span
+    /// of their expression, and statements without a semicolon or modules
+    /// containing a single expression have the same span as it, so a bare
+    /// metavariable only compares the expression.
+    fn is_candidate(&self, node: &Value) -> bool {
+        match self.kind {
+            Kind::AnyExpr => {
+                node.get("span").is_some()
+                    && !matches!(
+                        get_type(node),
+                        Some("Module" | "ExpressionStatement")
+                    )
+            }
+            _ => get_type(node) == get_type(&self.root),
+        }
+    }
+
+    fn match_node<'a>(
+        &self,
+        pattern: &Value,
+        node: &'a Value,
+        bindings: &mut Vec<(String, Capture<'a>)>,
+    ) -> bool {
+        let pattern = unwrap_parens(pattern);
+
+        // Metavariables bind nodes with their parentheses, so the grouping is
+        // kept when the node is pasted into a template
+        if let Some(name) = get_metavariable(pattern) {
+            return bind(name, Capture::Node(node), bindings);
+        }
+
+        let node = unwrap_parens(node);
+
+        match (pattern, node) {
+            (Value::Object(pattern), Value::Object(_)) => {
+                self.match_object(pattern, node, bindings)
+            }
+            (Value::Array(pattern), Value::Array(nodes)) => {
+                self.match_list(pattern, nodes, bindings)
+            }
+            _ => pattern == node,
+        }
+    }
+
+    fn match_object<'a>(
+        &self,
+        pattern: &Map<String, Value>,
+        node: &'a Value,
+        bindings: &mut Vec<(String, Capture<'a>)>,
+    ) -> bool {
+        let pattern_type = pattern.get("type").and_then(Value::as_str);
+        let node_type = node.get("type").and_then(Value::as_str);
+
+        match (pattern_type, node_type) {
+            // Elements without children in the pattern match elements with any
+            // children. Self-closing elements match empty elements.
+            (Some("JSXElement"), Some("JSXElement")) => {
+                let children = pattern["children"].as_array();
+                let has_children = children.is_some_and(|c| !c.is_empty());
+
+                return self.match_node(
+                    &pattern["opening"],
+                    &node["opening"],
+                    bindings,
+                ) && ((!self.exact && !has_children)
+                    || self.match_node(
+                        &pattern["children"],
+                        &node["children"],
+                        bindings,
+                    ));
+            }
+            // Attributes are matched in any order, and elements can have
+            // attributes which aren't in the pattern unless matching exactly
+            (Some("JSXOpeningElement"), Some("JSXOpeningElement")) => {
+                return self.match_node(
+                    &pattern["name"],
+                    &node["name"],
+                    bindings,
+                ) && self.match_optional(
+                    &pattern["typeArguments"],
+                    &node["typeArguments"],
+                    bindings,
+                ) && self.match_attributes(
+                    pattern["attributes"].as_array().map_or(&[], Vec::as_slice),
+                    &node["attributes"]
+                        .as_array()
+                        .map_or(vec![], |nodes| nodes.iter().collect()),
+                    bindings,
+                );
+            }
+            // A metavariable in an expression container matches attribute values
+            // which aren't expressions, such as strings
+            (Some("JSXExpressionContainer"), node_type)
+                if node_type != Some("JSXExpressionContainer") =>
+            {
+                return match get_metavariable(&pattern["expression"]) {
+                    Some(name) => bind(name, Capture::Node(node), bindings),
+                    None => false,
+                };
+            }
+            _ => {}
+        }
+
+        pattern
+            .iter()
+            .filter(|(key, _)| !IGNORED_KEYS.contains(&key.as_str()))
+            .all(|(key, value)| {
+                let node = node.get(key).unwrap_or(&Value::Null);
+
+                match TYPE_KEYS.contains(&key.as_str()) {
+                    true => self.match_optional(value, node, bindings),
+                    false => self.match_node(value, node, bindings),
+                }
+            })
+    }
+
+    /// Match optional type information, where anything matches an omitted value
+    fn match_optional<'a>(
+        &self,
+        pattern: &Value,
+        node: &'a Value,
+        bindings: &mut Vec<(String, Capture<'a>)>,
+    ) -> bool {
+        pattern.is_null() || self.match_node(pattern, node, bindings)
+    }
+
+    /// Match a list of nodes, where variadic metavariables match any number of
+    /// nodes
+    fn match_list<'a>(
+        &self,
+        pattern: &[Value],
+        nodes: &'a [Value],
+        bindings: &mut Vec<(String, Capture<'a>)>,
+    ) -> bool {
+        let (first, rest) = match pattern.split_first() {
+            Some(split) => split,
+            None => return nodes.is_empty(),
+        };
+
+        if let Some(name) = get_variadic(first) {
+            for count in 0..=nodes.len() {
+                let mut trial = bindings.clone();
+                let binding = Capture::Nodes(&nodes[..count]);
+
+                if bind(name, binding, &mut trial)
+                    && self.match_list(rest, &nodes[count..], &mut trial)
+                {
+                    *bindings = trial;
+                    return true;
+                }
+            }
+
+            return false;
+        }
+
+        match nodes.split_first() {
+            Some((node, nodes)) => {
+                self.match_node(first, node, bindings)
+                    && self.match_list(rest, nodes, bindings)
+            }
+            None => false,
+        }
+    }
+
+    /// Match each attribute of the pattern to a different attribute of the node
+    fn match_attributes<'a>(
+        &self,
+        pattern: &[Value],
+        nodes: &[&'a Value],
+        bindings: &mut Vec<(String, Capture<'a>)>,
+    ) -> bool {
+        let (first, rest) = match pattern.split_first() {
+            Some(split) => split,
+            None => return !self.exact || nodes.is_empty(),
+        };
+
+        // Variadic attributes match the attributes which aren't otherwise
+        // matched, so they're matched last
+        if let Some(name) = get_variadic(first) {
+            if rest.iter().any(|attr| get_variadic(attr).is_none()) {
+                let mut pattern = rest.to_vec();
+                pattern.push(first.clone());
+
+                return self.match_attributes(&pattern, nodes, bindings);
+            }
+
+            return bind(name, Capture::Attributes(nodes.to_vec()), bindings)
+                && self.match_attributes(rest, &[], bindings);
+        }
+
+        for (index, node) in nodes.iter().enumerate() {
+            let mut trial = bindings.clone();
+            let mut remaining = nodes.to_vec();
+            remaining.remove(index);
+
+            if self.match_node(first, node, &mut trial)
+                && self.match_attributes(rest, &remaining, &mut trial)
+            {
+                *bindings = trial;
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// Visits the nodes of a module which can match a pattern, collecting their
+/// spans
+struct Finder<'a> {
+    pattern: &'a Pattern,
+    candidates: HashSet<(BytePos, BytePos)>,
+}
+
+impl Finder<'_> {
+    fn add(&mut self, span: Span) {
+        self.candidates.insert((span.lo, span.hi));
+    }
+}
+
+impl Visit for Finder<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match self.pattern.kind {
+            Kind::Expr(kind) if kind == discriminant(expr) => {
+                self.add(expr.span())
+            }
+            Kind::AnyExpr => self.add(expr.span()),
+            _ => {}
+        }
+
+        expr.visit_children_with(self);
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        if self.pattern.kind == Kind::Ident {
+            self.add(ident.span());
+        }
+    }
+
+    fn visit_jsx_element(&mut self, element: &JSXElement) {
+        if self.pattern.kind == Kind::JSXElement {
+            self.add(element.span());
+        }
+
+        element.visit_children_with(self);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if self.pattern.kind == Kind::Stmt(discriminant(stmt)) {
+            self.add(stmt.span());
+        }
+
+        stmt.visit_children_with(self);
+    }
+
+    fn visit_var_decl(&mut self, decl: &VarDecl) {
+        if self.pattern.kind == Kind::VarDecl {
+            self.add(decl.span());
+        }
+
+        decl.visit_children_with(self);
+    }
+
+    fn visit_decl(&mut self, decl: &Decl) {
+        if self.pattern.kind == Kind::Decl(discriminant(decl)) {
+            self.add(decl.span());
+        }
+
+        decl.visit_children_with(self);
+    }
+
+    fn visit_module_decl(&mut self, decl: &ModuleDecl) {
+        if self.pattern.kind == Kind::ModuleDecl(discriminant(decl)) {
+            self.add(decl.span());
+        }
+
+        decl.visit_children_with(self);
+    }
+}
+
+/// A replacement for code matching a pattern, where metavariables are
+/// replaced with the code they matched. Other text is kept as is.
+#[derive(Clone)]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Clone)]
+enum TemplatePart {
+    Text(String),
+    Metavariable(String),
+}
+
+impl Template {
+    /// Parse a template, checking that its metavariables are in the pattern
+    pub fn parse(source: &str, pattern: &Pattern) -> Result<Template, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+
+        while let Some(index) = rest.find('$') {
+            text.push_str(&rest[..index]);
+            rest = &rest[index..];
+
+            let dollars = rest.len() - rest.trim_start_matches('$').len();
+            let name: String = rest[dollars..]
+                .chars()
+                .take_while(|c| {
+                    c.is_ascii_uppercase() || c.is_ascii_digit() || *c == '_'
+                })
+                .collect();
+
+            // Other identifiers starting with `$` are kept as is
+            if !(dollars == 1 || dollars == 3) || !is_name(&name) {
+                text.push_str(&rest[..dollars]);
+                rest = &rest[dollars..];
+                continue;
+            }
+
+            if !pattern.names.contains(&name) {
+                return Err(format!(
+                    "Unknown metavariable {}{}",
+                    &rest[..dollars],
+                    name
+                ));
+            }
+
+            parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+            parts.push(TemplatePart::Metavariable(name.clone()));
+            rest = &rest[dollars + name.len()..];
+        }
+
+        text.push_str(rest);
+        parts.push(TemplatePart::Text(text));
+
+        Ok(Template { parts })
+    }
+
+    /// Render the template with the code bound to the metavariables of a
+    /// match, where `source` gets the code of a span
+    pub fn render(
+        &self,
+        m: &PatternMatch,
+        source: &dyn Fn(Span) -> String,
+    ) -> String {
+        let mut output = String::new();
+
+        for (index, part) in self.parts.iter().enumerate() {
+            match part {
+                TemplatePart::Text(text) => output.push_str(text),
+                TemplatePart::Metavariable(name) => {
+                    let binding = m
+                        .bindings
+                        .iter()
+                        .find(|(bound, _)| bound == name)
+                        .map(|(_, binding)| binding);
+
+                    match binding {
+                        // Empty attributes don't leave the whitespace
+                        // separating them from other attributes
+                        Some(Binding::Attributes(attributes))
+                            if attributes.is_empty() =>
+                        {
+                            output.truncate(output.trim_end().len());
+                        }
+                        // Compound expressions are wrapped in parentheses
+                        // unless they are on their own, such as an argument
+                        Some(
+                            binding @ Binding::Node { compound: true, .. },
+                        ) if !self.is_standalone(index, &output) => {
+                            let text = binding.source(source);
+                            output.push_str(&format!("({})", text));
+                        }
+                        Some(binding) => {
+                            output.push_str(&binding.source(source))
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Check if the metavariable at an index is separated from the code
+    /// around it, so that an expression pasted there keeps its meaning
+    fn is_standalone(&self, index: usize, output: &str) -> bool {
+        let before = output.trim_end();
+        let after = match self.parts.get(index + 1) {
+            Some(TemplatePart::Text(text)) => text.trim_start(),
+            _ => "",
+        };
+
+        let is_separated_before = match before.chars().last() {
+            None | Some('(' | '[' | '{' | ',' | ';') => true,
+            Some('=') => !before[..before.len() - 1].ends_with([
+                '=', '!', '<', '>', '+', '-', '*', '/', '%', '&', '|', '^', '?',
+            ]),
+            _ => false,
+        };
+
+        is_separated_before
+            && (after.is_empty()
+                || after.starts_with([')', ']', '}', ',', ';']))
+    }
+}
+
+impl Binding {
+    /// Get the source text of the bound nodes. The text of a list of nodes
+    /// includes the separators between them, while attributes are separated
+    /// by a space.
+    pub fn text(&self, file: &SourceFile) -> String {
+        self.source(&|span| edit::source_text(file, span).to_string())
+    }
+
+    /// Get the code of the bound nodes, where `source` gets the code of a
+    /// span
+    fn source(&self, source: &dyn Fn(Span) -> String) -> String {
+        match self {
+            Binding::Node { span, .. } | Binding::Nodes(span) => {
+                span.map(source).unwrap_or_default()
+            }
+            Binding::Attributes(spans) => spans
+                .iter()
+                .map(|span| source(*span))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+impl Capture<'_> {
+    fn nodes(&self) -> Vec<&Value> {
+        match self {
+            Capture::Node(node) => vec![node],
+            Capture::Nodes(nodes) => nodes.iter().collect(),
+            Capture::Attributes(nodes) => nodes.clone(),
+        }
+    }
+
+    fn eq(&self, other: &Capture) -> bool {
+        let (a, b) = (self.nodes(), other.nodes());
+
+        discriminant(self) == discriminant(other)
+            && a.len() == b.len()
+            && a.iter().zip(b).all(|(a, b)| eq_ignore_span(a, b))
+    }
+
+    /// Get the code bound to the metavariable once the match is complete
+    fn binding(&self) -> Binding {
+        match self {
+            Capture::Node(node) => Binding::Node {
+                span: get_span(node),
+                compound: is_compound(node),
+            },
+            Capture::Nodes(nodes) => Binding::Nodes(
+                nodes.first().and_then(get_span).and_then(|first| {
+                    let last = get_span(nodes.last()?)?;
+                    Some(Span { hi: last.hi, ..first })
+                }),
+            ),
+            Capture::Attributes(nodes) => Binding::Attributes(
+                nodes.iter().filter_map(|node| get_span(node)).collect(),
+            ),
+        }
+    }
+}
+
+/// Find the metavariables in a pattern and their position
+fn find_metavariables(node: &Value, names: &mut Vec<(BytePos, String)>) {
+    let name = get_variadic(node).or_else(|| get_metavariable(node));
+
+    if let (Some(name), Some(span)) = (name, get_span(node)) {
+        names.push((span.lo, name.to_string()));
+    }
+
+    match node {
+        Value::Object(object) => {
+            object.values().for_each(|value| find_metavariables(value, names))
+        }
+        Value::Array(items) => {
+            items.iter().for_each(|item| find_metavariables(item, names))
+        }
+        _ => {}
+    }
+}
+
+/// Bind nodes to a metavariable. Metavariables used more than once must
+/// match the same nodes each time.
+fn bind<'a>(
+    name: &str,
+    binding: Capture<'a>,
+    bindings: &mut Vec<(String, Capture<'a>)>,
+) -> bool {
+    if name == "_" {
+        return true;
+    }
+
+    match bindings.iter().find(|(bound, _)| bound == name) {
+        Some((_, bound)) => bound.eq(&binding),
+        None => {
+            bindings.push((name.to_string(), binding));
+            true
+        }
+    }
+}
+
+/// Get the kind of nodes which can match a pattern, ignoring the parentheses
+/// around it
+fn get_kind(item: &ModuleItem) -> Kind {
+    let mut expr = match item {
+        ModuleItem::ModuleDecl(decl) => {
+            return Kind::ModuleDecl(discriminant(decl))
+        }
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(_))) => return Kind::VarDecl,
+        ModuleItem::Stmt(Stmt::Decl(decl)) => {
+            return Kind::Decl(discriminant(decl))
+        }
+        ModuleItem::Stmt(Stmt::Expr(stmt)) => &*stmt.expr,
+        ModuleItem::Stmt(stmt) => return Kind::Stmt(discriminant(stmt)),
+    };
+
+    while let Expr::Paren(paren) = expr {
+        expr = &paren.expr;
+    }
+
+    match expr {
+        Expr::Ident(_) => Kind::Ident,
+        Expr::JSXElement(_) => Kind::JSXElement,
+        expr => Kind::Expr(discriminant(expr)),
+    }
+}
+
+fn get_type(node: &Value) -> Option<&str> {
+    node.get("type").and_then(Value::as_str)
+}
+
+/// Get the span of a node. Arguments and array elements don't have their own
+/// span, so the span of their expression is used.
+fn get_span(node: &Value) -> Option<Span> {
+    let pos = |value: &Value, key| {
+        value.get(key).and_then(Value::as_u64).map(|pos| BytePos(pos as u32))
+    };
+
+    match node.get("span") {
+        Some(span) => Some(Span {
+            lo: pos(span, "start")?,
+            hi: pos(span, "end")?,
+            ..DUMMY_SP
+        }),
+        None => {
+            let span = get_span(node.get("expression")?)?;
+
+            match node.get("spread").and_then(|spread| pos(spread, "start")) {
+                Some(lo) => Some(Span { lo, ..span }),
+                None => Some(span),
+            }
+        }
+    }
+}
+
+/// Get the name of a metavariable, such as `NAME` for `$NAME`
+fn get_metavariable(node: &Value) -> Option<&str> {
+    get_identifier(node)?.strip_prefix('$').filter(|name| is_name(name))
+}
+
+/// Get the name of a variadic metavariable in a list, such as `ARGS` for
+/// `$$$ARGS`. Arguments and statements wrap the identifier, JSX children are
+/// written as text and JSX attributes as boolean attributes. The unnamed `$$$`
+/// metavariable matches without binding a value like `$_`.
+fn get_variadic(node: &Value) -> Option<&str> {
+    let name = match get_type(node) {
+        Some("ExpressionStatement" | "JSXExpressionContainer") => {
+            get_identifier(&node["expression"])?
+        }
+        Some("JSXText") => node["value"].as_str()?.trim(),
+        Some("JSXAttribute") if node["value"].is_null() => {
+            get_identifier(&node["name"])?
+        }
+        None if node.get("spread").is_some_and(Value::is_null) => {
+            get_identifier(&node["expression"])?
+        }
+        _ => get_identifier(node)?,
+    };
+
+    match name.strip_prefix("$$$")? {
+        "" => Some("_"),
+        name => is_name(name).then_some(name),
+    }
+}
+
+fn get_identifier(node: &Value) -> Option<&str> {
+    match get_type(node) {
+        Some("Identifier") => node["value"].as_str(),
+        _ => None,
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn is_compound(node: &Value) -> bool {
+    get_type(node).is_some_and(|kind| COMPOUND_TYPES.contains(&kind))
+}
+
+fn unwrap_parens(node: &Value) -> &Value {
+    match get_type(node) {
+        Some("ParenthesisExpression") => unwrap_parens(&node["expression"]),
+        _ => node,
+    }
+}
+
+fn eq_ignore_span(a: &Value, b: &Value) -> bool {
+    match (unwrap_parens(a), unwrap_parens(b)) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys = |object: &Map<String, Value>| {
+                object
+                    .keys()
+                    .filter(|key| !IGNORED_KEYS.contains(&key.as_str()))
+                    .count()
+            };
+
+            keys(a) == keys(b)
+                && a.iter()
+                    .filter(|(key, _)| !IGNORED_KEYS.contains(&key.as_str()))
+                    .all(|(key, a)| {
+                        b.get(key).is_some_and(|b| eq_ignore_span(a, b))
+                    })
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len()
+                && a.iter().zip(b).all(|(a, b)| eq_ignore_span(a, b))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// Wrap metavariables used as JSX attribute values in braces, such as
+/// `name=$VALUE` to `name={$VALUE}`, returning the names of the wrapped
+/// metavariables
+fn wrap_attribute_values(source: &str) -> (String, Vec<String>) {
+    let mut output = String::with_capacity(source.len());
+    let mut names = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        output.push(c);
+
+        if c == '=' && chars.peek() == Some(&'$') {
+            let mut name = String::new();
+
+            while let Some(&c) = chars.peek() {
+                if !(c == '$' || c == '_' || c.is_ascii_alphanumeric()) {
+                    break;
+                }
+
+                name.push(c);
+                chars.next();
+            }
+
+            output.push_str(&format!("{{{}}}", name));
+            names.push(name.trim_start_matches('$').to_string());
+        }
+    }
+
+    (output, names)
+}
+
+/// Replace the values of attributes written as `name=$VALUE` with their
+/// metavariable, so it matches the whole value including the braces of
+/// expressions rather than the expression inside them
+fn unwrap_attribute_values(node: &mut Value, names: &[String]) {
+    if get_type(node) == Some("JSXAttribute") {
+        let expression = node["value"]["expression"].clone();
+        let is_wrapped = get_metavariable(&expression)
+            .is_some_and(|name| names.iter().any(|n| n == name));
+
+        if is_wrapped {
+            node["value"] = expression;
+        }
+    }
+
+    match node {
+        Value::Object(object) => object
+            .values_mut()
+            .for_each(|value| unwrap_attribute_values(value, names)),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| unwrap_attribute_values(item, names)),
+        _ => {}
+    }
+}
+
+/// Patterns are matched against the serialized AST, so these tests pin the
+/// parts of its representation which the matcher relies on
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serialize the first item of a module. Type assertions such as `<T>a`
+    /// are only parsed without JSX.
+    fn serialize(source: &str) -> Value {
+        let options = ParserOptions::default();
+        let module = parser::parse_pattern(source, options)
+            .or_else(|_| {
+                let options = ParserOptions { jsx: false, ..options };
+                parser::parse_pattern(source, options)
+            })
+            .expect("failed to parse");
+
+        serde_json::to_value(&module.body[0]).expect("failed to serialize")
+    }
+
+    fn expression(source: &str) -> Value {
+        let stmt = serialize(source);
+        assert_eq!(get_type(&stmt), Some("ExpressionStatement"));
+
+        stmt["expression"].clone()
+    }
+
+    #[test]
+    fn identifiers() {
+        let node = expression("foo");
+
+        assert_eq!(get_identifier(&node), Some("foo"));
+        assert_eq!(get_metavariable(&expression("$FOO")), Some("FOO"));
+    }
+
+    #[test]
+    fn spans() {
+        let node = expression("foo");
+        let span = get_span(&node).expect("missing span");
+
+        assert_eq!(span.hi.0 - span.lo.0, 3);
+    }
+
+    #[test]
+    fn parentheses() {
+        let node = expression("(a + b)");
+
+        assert_eq!(get_type(&node), Some("ParenthesisExpression"));
+        assert_eq!(get_type(unwrap_parens(&node)), Some("BinaryExpression"));
+    }
+
+    #[test]
+    fn arguments() {
+        let node = expression("foo(a, ...$$$ARGS)");
+        let args = node["arguments"].as_array().expect("missing arguments");
+
+        assert!(args[0]["spread"].is_null());
+        assert_eq!(get_variadic(&args[0]), None);
+        assert_eq!(get_span(&args[0]), get_span(&args[0]["expression"]));
+        assert!(get_span(&args[1]).is_some_and(|span| {
+            span.lo.0 + 3 == get_span(&args[1]["expression"]).unwrap().lo.0
+        }));
+    }
+
+    #[test]
+    fn variadic_statements() {
+        let node = expression("() => { $$$BODY }");
+        let stmts = node["body"]["stmts"].as_array().expect("missing body");
+
+        assert_eq!(get_variadic(&stmts[0]), Some("BODY"));
+    }
+
+    #[test]
+    fn type_keys() {
+        let call = expression("foo<T>()");
+        let decl = serialize("function foo<T>(a: T) {}");
+
+        assert!(!call["typeArguments"].is_null());
+        assert!(!decl["typeParameters"].is_null());
+        assert!(!decl["params"][0]["pat"]["typeAnnotation"].is_null());
+        assert!(expression("foo()")["typeArguments"].is_null());
+    }
+
+    #[test]
+    fn literals() {
+        let node = expression("'a'");
+
+        assert_eq!(get_type(&node), Some("StringLiteral"));
+        assert!(node.get("raw").is_some());
+    }
+
+    #[test]
+    fn jsx() {
+        let node = expression("<Foo a b={1}>$$$CHILDREN</Foo>");
+        let opening = &node["opening"];
+        let attributes = opening["attributes"].as_array().unwrap();
+
+        assert_eq!(get_type(&node), Some("JSXElement"));
+        assert_eq!(get_type(opening), Some("JSXOpeningElement"));
+        assert_eq!(get_identifier(&opening["name"]), Some("Foo"));
+        assert!(opening.get("typeArguments").is_some());
+
+        assert_eq!(get_type(&attributes[0]), Some("JSXAttribute"));
+        assert!(attributes[0]["value"].is_null());
+        assert_eq!(get_identifier(&attributes[0]["name"]), Some("a"));
+        assert_eq!(
+            get_type(&attributes[1]["value"]),
+            Some("JSXExpressionContainer")
+        );
+
+        let children = node["children"].as_array().unwrap();
+        assert_eq!(get_type(&children[0]), Some("JSXText"));
+        assert_eq!(get_variadic(&children[0]), Some("CHILDREN"));
+    }
+
+    #[test]
+    fn compound_types() {
+        // Await and yield expressions are only valid inside functions, so
+        // they are found by their path in the function
+        let sources = [
+            ("() => a", ""),
+            ("a = b", ""),
+            ("async () => await a", "/body"),
+            ("a + b", ""),
+            ("(class {})", ""),
+            ("a ? b : c", ""),
+            ("(function () {})", ""),
+            ("new A()", ""),
+            ("1", ""),
+            ("a, b", ""),
+            ("a as T", ""),
+            ("a satisfies T", ""),
+            ("<T>a", ""),
+            ("!a", ""),
+            ("a++", ""),
+            ("(function* () { yield a })", "/body/stmts/0/expression"),
+        ];
+
+        let mut types: Vec<String> = sources
+            .iter()
+            .map(|(source, path)| {
+                let node = unwrap_parens(&expression(source)).clone();
+                let node = node.pointer(path).expect("missing node");
+
+                assert!(is_compound(node));
+                get_type(node).unwrap().to_string()
+            })
+            .collect();
+        types.sort();
+
+        assert_eq!(types, COMPOUND_TYPES);
+    }
+}
@@ -1,25 +1,151 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
 
 use console::style;
+use globset::GlobSet;
 use ignore::types::{Types, TypesBuilder};
 use ignore::WalkBuilder;
-use swc_common::Span;
+use swc_common::{FileName, Loc, SourceFile, SourceMap, Span, Spanned};
 
+use crate::config::Severity;
+use crate::edit::{self, Edit, EditMode};
 use crate::logger::Logger;
-use crate::parser::{self, ParseError, ParsedModule};
+use crate::parser::{self, ParseError, ParsedModule, ParserOptions};
+use crate::utils;
 
 pub trait ProcessorRequest {
     fn path(&self) -> &PathBuf;
-    fn analyze(&self, module: &ParsedModule) -> Vec<Span>;
+    fn analyze(&mut self, module: &ParsedModule) -> Vec<Match>;
+
+    /// Whether matches depend on other modules, such as for requests building
+    /// a module graph. When only searching changed files, every module is
+    /// still analyzed for these requests, but only matches in changed files
+    /// are reported.
+    fn uses_all_modules(&self) -> bool {
+        false
+    }
+
+    /// Get the edits to make to a module for requests which change files.
+    /// The edits are applied according to the edit mode, and each edit is
+    /// logged as a match.
+    fn edit(&mut self, _module: &ParsedModule) -> Vec<Edit> {
+        vec![]
+    }
+
+    /// Called after all modules have been analyzed. Requests which need to
+    /// look at every module before reporting can return their matches here,
+    /// located using `processor::locate`.
+    fn finish(&mut self) -> Vec<(String, Loc)> {
+        vec![]
+    }
+
+    /// Forget a module which changed or was deleted. Requests are kept between
+    /// runs when watching files, so requests which collect modules, such as
+    /// into a module graph, remove it before it is analyzed again.
+    fn forget(&mut self, _path: &Path) {}
+
+    /// Get the errors which make the results of the request incomplete, such
+    /// as invalid files other than modules. Called after `finish`, and each
+    /// error is printed and counted as an error of the run.
+    fn errors(&mut self) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// A match found while analyzing a module. By default, the source line
+/// containing the match is logged, but requests can provide their own text.
+#[derive(Clone)]
+pub struct Match {
+    pub span: Span,
+    pub text: Option<String>,
+    /// Matches fail the run unless they are warnings
+    pub severity: Severity,
+}
+
+impl From<Span> for Match {
+    fn from(span: Span) -> Self {
+        Match { span, text: None, severity: Severity::Error }
+    }
+}
+
+/// The result of processing all modules
+#[derive(Clone, Copy)]
+pub struct Summary {
+    /// The number of matches logged
+    pub matches: usize,
+    /// The number of matches logged which fail the run, which excludes
+    /// warnings
+    pub failures: usize,
+    /// The number of files which couldn't be loaded or parsed
+    pub errors: usize,
+}
+
+/// Options for finding and parsing the modules to process
+#[derive(Default)]
+pub struct ProcessorOptions {
+    /// The files and directories to search. The request path is searched if
+    /// empty.
+    pub paths: Vec<PathBuf>,
+    /// Globs matching the absolute paths of the files to process. All files
+    /// are processed if empty.
+    pub include: GlobSet,
+    /// Globs matching the absolute paths of files to skip
+    pub exclude: GlobSet,
+    /// The extensions of the files to process. The JavaScript and TypeScript
+    /// extensions are used if empty.
+    pub extensions: Vec<String>,
+    pub parser: ParserOptions,
+    /// Search files ignored by `.gitignore` and other ignore files
+    pub no_ignore: bool,
+    /// The canonical paths of the changed files to search. All files are
+    /// searched if not set.
+    pub changed: Option<HashSet<PathBuf>>,
+    /// How edits are applied for requests which change files
+    pub edit_mode: EditMode,
+}
+
+impl ProcessorOptions {
+    fn is_included(&self, path: &Path) -> bool {
+        let path = utils::absolutize(path);
+
+        (self.include.is_empty() || self.include.is_match(&path))
+            && !self.exclude.is_match(&path)
+    }
+
+    fn is_changed(&self, path: &Path) -> bool {
+        match &self.changed {
+            Some(changed) => {
+                path.canonicalize().is_ok_and(|path| changed.contains(&path))
+            }
+            None => true,
+        }
+    }
 }
 
+/// Parsed modules and the modification time of their file, so unchanged
+/// modules aren't parsed again when processing repeatedly.
+pub type ParseCache = HashMap<PathBuf, (SystemTime, Rc<ParsedModule>)>;
+
 pub struct Processor<'a, L, R>
 where
     L: Logger,
     R: ProcessorRequest,
 {
-    request: R,
+    /// The requests run against each module, labeled with their name when
+    /// running a batch of requests
+    requests: Vec<(Option<String>, R)>,
     logger: &'a mut L,
+    options: &'a ProcessorOptions,
+    cache: Option<&'a mut ParseCache>,
+    /// The paths of the processed modules
+    modules: HashSet<PathBuf>,
+    summary: Summary,
+    /// Whether the remaining hunks are skipped in interactive mode
+    skip_edits: bool,
 }
 
 impl<'a, L, R> Processor<'a, L, R>
@@ -27,52 +153,287 @@ where
     L: Logger,
     R: ProcessorRequest,
 {
-    pub fn new(request: R, logger: &'a mut L) -> Processor<'a, L, R> {
-        Processor { logger, request }
+    pub fn new(
+        request: R,
+        logger: &'a mut L,
+        options: &'a ProcessorOptions,
+    ) -> Processor<'a, L, R> {
+        Processor {
+            logger,
+            requests: vec![(None, request)],
+            options,
+            cache: None,
+            modules: HashSet::new(),
+            summary: Summary { matches: 0, failures: 0, errors: 0 },
+            skip_edits: false,
+        }
+    }
+
+    /// Run several requests against each module, so that modules are only
+    /// parsed once. Matches are logged with the name of their request.
+    pub fn batch(
+        requests: Vec<(String, R)>,
+        logger: &'a mut L,
+        options: &'a ProcessorOptions,
+    ) -> Processor<'a, L, R> {
+        Processor {
+            logger,
+            requests: requests
+                .into_iter()
+                .map(|(name, request)| (Some(name), request))
+                .collect(),
+            options,
+            cache: None,
+            modules: HashSet::new(),
+            summary: Summary { matches: 0, failures: 0, errors: 0 },
+            skip_edits: false,
+        }
+    }
+
+    /// Reuse parsed modules from previous runs when their file hasn't changed
+    pub fn with_cache(mut self, cache: &'a mut ParseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Process all modules and get the number of matches and errors
+    pub fn process(&mut self) -> Summary {
+        let (_, request) = &self.requests[0];
+        let uses_all_modules =
+            self.requests.iter().any(|(_, request)| request.uses_all_modules());
+
+        let files = match walk(request.path(), uses_all_modules, self.options) {
+            Ok(files) => files,
+            Err(err) => {
+                eprintln!("{}: {}", style("error").red().bold(), err);
+                self.summary.errors += 1;
+                return self.summary;
+            }
+        };
+
+        self.modules = files.iter().cloned().collect();
+
+        for file in files {
+            match self.parse(&file) {
+                Ok(parsed) => self.analyze(&parsed),
+                Err(err) => self.print_error(&file, err),
+            }
+        }
+
+        for index in 0..self.requests.len() {
+            for (text, loc) in self.requests[index].1.finish() {
+                self.log(index, text, loc, Severity::Error);
+            }
+
+            for err in self.requests[index].1.errors() {
+                eprintln!("{}: {}", style("error").red().bold(), err);
+                self.summary.errors += 1;
+            }
+        }
+
+        self.summary
     }
 
-    pub fn process(&mut self) {
-        let matcher = build_matcher().unwrap();
+    fn parse(&mut self, path: &Path) -> Result<Rc<ParsedModule>, ParseError> {
+        let cache = match &mut self.cache {
+            Some(cache) => cache,
+            None => {
+                return parser::parse(path, self.options.parser).map(Rc::new)
+            }
+        };
+
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(ParseError::IO)?;
+
+        if let Some((time, parsed)) = cache.get(path) {
+            if *time == modified {
+                return Ok(parsed.clone());
+            }
+        }
+
+        let parsed = Rc::new(parser::parse(path, self.options.parser)?);
+        cache.insert(path.to_path_buf(), (modified, parsed.clone()));
 
-        WalkBuilder::new(&self.request.path())
-            .hidden(false)
-            .types(matcher)
-            .build()
-            .filter_map(|entry| entry.ok())
-            .filter(|file| file.file_type().map_or(false, |ft| ft.is_file()))
-            .for_each(|file| match parser::parse(&file.path()) {
-                Ok(parsed) => self.analyze(parsed),
-                Err(err) => self.print_error(&file.path(), err),
-            });
+        Ok(parsed)
     }
 
-    fn analyze(&mut self, parsed: ParsedModule) {
+    fn analyze(&mut self, parsed: &ParsedModule) {
         let source = &parsed.source_map;
 
-        self.request
-            .analyze(&parsed)
+        for index in 0..self.requests.len() {
+            for m in self.requests[index].1.analyze(parsed) {
+                let severity = m.severity;
+
+                if let Some((text, loc)) = locate(source, m) {
+                    self.log(index, text, loc, severity);
+                }
+            }
+
+            let edits = self.requests[index].1.edit(parsed);
+            if !edits.is_empty() {
+                self.write_edits(index, parsed, edits);
+            }
+        }
+    }
+
+    fn write_edits(
+        &mut self,
+        index: usize,
+        parsed: &ParsedModule,
+        mut edits: Vec<Edit>,
+    ) {
+        edits.sort_by_key(|edit| (edit.span.lo, edit.span.hi));
+        let file = parsed.source_map.lookup_source_file(edits[0].span.lo);
+
+        if let Some(edit) = edit::find_overlap(&edits) {
+            let loc = parsed.source_map.lookup_char_pos(edit.span.lo);
+
+            self.summary.errors += 1;
+            eprintln!(
+                "{}: Conflicting edits in {}:{}:{}",
+                style("error").red().bold(),
+                parsed.path.display(),
+                loc.line,
+                loc.col.0 + 1
+            );
+            return;
+        }
+
+        match self.options.edit_mode {
+            EditMode::DryRun => {
+                print!("{}", edit::render_diff(&parsed.path, &file, &edits));
+
+                // The diff is printed instead of logging the edits
+                self.summary.matches += edits.len();
+                self.summary.failures += edits.len();
+                return;
+            }
+            EditMode::Check => {}
+            EditMode::Write => {
+                if !self.write(&parsed.path, edit::apply(&file, &edits)) {
+                    return;
+                }
+            }
+            EditMode::Interactive => {
+                edits = self.prompt_edits(&parsed.path, &file, edits);
+
+                if edits.is_empty()
+                    || !self.write(&parsed.path, edit::apply(&file, &edits))
+                {
+                    return;
+                }
+            }
+        }
+
+        for m in edits.into_iter().map(|edit| Match::from(edit.span)) {
+            if let Some((text, loc)) = locate(&parsed.source_map, m) {
+                self.log(index, text, loc, Severity::Error);
+            }
+        }
+    }
+
+    fn write(&mut self, path: &Path, contents: String) -> bool {
+        match fs::write(path, contents) {
+            Ok(_) => true,
+            Err(err) => {
+                self.summary.errors += 1;
+                eprintln!(
+                    "{}: Failed to write {}: {}",
+                    style("error").red().bold(),
+                    path.display(),
+                    err
+                );
+                false
+            }
+        }
+    }
+
+    /// Print each hunk of edits and prompt whether to apply it, returning the
+    /// accepted edits. Quitting skips the hunks of the remaining files too.
+    fn prompt_edits(
+        &mut self,
+        path: &Path,
+        file: &SourceFile,
+        edits: Vec<Edit>,
+    ) -> Vec<Edit> {
+        if self.skip_edits {
+            return vec![];
+        }
+
+        let mut accepted = HashSet::new();
+        let mut offset = 0;
+
+        print!("{}", edit::render_header(path));
+
+        for hunk in edit::hunks(file, &edits) {
+            print!("{}", hunk.render(offset));
+            print!("{} ", style("Apply this change [y,n,q]?").blue().bold());
+            let _ = io::stdout().flush();
+
+            let mut answer = String::new();
+            let answer = match io::stdin().read_line(&mut answer) {
+                Ok(0) | Err(_) => "q",
+                Ok(_) => answer.trim(),
+            };
+
+            match answer {
+                "y" | "Y" => {
+                    accepted.extend(hunk.edits.clone());
+                    offset += hunk.delta();
+                }
+                "q" | "Q" => {
+                    self.skip_edits = true;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        edits
             .into_iter()
-            .filter_map(|span| {
-                let lines = match source.span_to_lines(span) {
-                    Ok(lines) => lines,
-                    Err(_) => return None,
-                };
+            .enumerate()
+            .filter(|(index, _)| accepted.contains(index))
+            .map(|(_, edit)| edit)
+            .collect()
+    }
+
+    /// Log a match of a request unless it is in a module which hasn't
+    /// changed. Matches in other files, such as unused dependencies in a
+    /// `package.json` manifest, depend on every module so they are always
+    /// logged.
+    fn log(
+        &mut self,
+        index: usize,
+        text: String,
+        loc: Loc,
+        severity: Severity,
+    ) {
+        if let FileName::Real(path) = &loc.file.name {
+            if self.modules.contains(path) && !self.options.is_changed(path) {
+                return;
+            }
+        }
+
+        if severity == Severity::Error {
+            self.summary.failures += 1;
+        }
 
-                let loc = source.lookup_char_pos(span.lo);
-                let line = lines.file.lookup_line(span.lo)?;
-                let text = lines.file.get_line(line)?.trim().to_string();
+        match &self.requests[index].0 {
+            Some(name) => self.logger.log_labeled(name, text, loc),
+            None => self.logger.log_with_severity(text, loc, severity),
+        }
 
-                Some((text, loc))
-            })
-            .for_each(|(text, loc)| self.logger.log(text, loc));
+        self.summary.matches += 1;
     }
 
-    fn print_error(&self, path: &Path, err: ParseError) {
+    fn print_error(&mut self, path: &Path, err: ParseError) {
+        self.summary.errors += 1;
         let error_text = style("error").red().bold();
 
         match err {
             ParseError::IO(err) => {
-                println!(
+                eprintln!(
                     "{}: Failed to load {} with status code {}.",
                     error_text,
                     path.display(),
@@ -80,11 +441,15 @@ where
                         .map_or("unknown".to_string(), |code| code.to_string())
                 )
             }
-            ParseError::Parse(err, _) => {
-                println!(
-                    "{}: Encountered parsing error while reading {}.\n  {}",
+            ParseError::Parse(err, source_map) => {
+                let loc = source_map.lookup_char_pos(err.span().lo);
+
+                eprintln!(
+                    "{}: Encountered parsing error while reading {}:{}:{}.\n  {}",
                     error_text,
                     path.display(),
+                    loc.line,
+                    loc.col.0 + 1,
                     style(err.kind().msg()).dim()
                 )
             }
@@ -92,12 +457,76 @@ where
     }
 }
 
+/// Get the location of a match and the text to log for it
+pub fn locate(source: &SourceMap, m: Match) -> Option<(String, Loc)> {
+    let lines = source.span_to_lines(m.span).ok()?;
+    let loc = source.lookup_char_pos(m.span.lo);
+    let text = match m.text {
+        Some(text) => text,
+        None => {
+            let line = lines.file.lookup_line(m.span.lo)?;
+            lines.file.get_line(line)?.trim().to_string()
+        }
+    };
+
+    Some((text, loc))
+}
+
+/// Find the files to process for a request. Only changed files are included
+/// when searching changed files, unless the request uses all modules.
+pub fn find_files<R: ProcessorRequest>(
+    request: &R,
+    options: &ProcessorOptions,
+) -> Result<Vec<PathBuf>, ignore::Error> {
+    walk(request.path(), request.uses_all_modules(), options)
+}
+
+/// Find the files to process in the search paths, or in `path` if there are
+/// none
+fn walk(
+    path: &Path,
+    uses_all_modules: bool,
+    options: &ProcessorOptions,
+) -> Result<Vec<PathBuf>, ignore::Error> {
+    let matcher = build_matcher(&options.extensions)?;
+    let mut paths = match options.paths.as_slice() {
+        [] => vec![path],
+        paths => paths.iter().map(PathBuf::as_path).collect(),
+    }
+    .into_iter();
+
+    let mut builder = WalkBuilder::new(paths.next().unwrap());
+    for path in paths {
+        builder.add(path);
+    }
+
+    Ok(builder
+        .standard_filters(!options.no_ignore)
+        .hidden(false)
+        .types(matcher)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|file| file.file_type().is_some_and(|ft| ft.is_file()))
+        .filter(|file| options.is_included(file.path()))
+        .filter(|file| uses_all_modules || options.is_changed(file.path()))
+        .map(|file| file.into_path())
+        .collect())
+}
+
 /// Build a file matcher for accepted file types. The `add_defaults` method is
-/// not used to avoid matching file types such as `*.vue` which are not yet
-/// supported by Flashlight.
-fn build_matcher() -> Result<Types, ignore::Error> {
+/// not used to avoid matching file types which aren't supported by Flashlight.
+fn build_matcher(extensions: &[String]) -> Result<Types, ignore::Error> {
     let mut builder = TypesBuilder::new();
 
+    if !extensions.is_empty() {
+        for ext in extensions {
+            builder
+                .add("custom", &format!("*.{}", ext.trim_start_matches('.')))?;
+        }
+
+        return builder.select("custom").build();
+    }
+
     // JS
     builder.add("js", "*.js")?;
     builder.add("js", "*.jsx")?;
@@ -111,5 +540,10 @@ fn build_matcher() -> Result<Types, ignore::Error> {
     builder.add("ts", "*.mts")?;
     builder.add("ts", "*.mts")?;
 
-    builder.select("js").select("ts").build()
+    // Single-file components
+    builder.add("sfc", "*.vue")?;
+    builder.add("sfc", "*.svelte")?;
+    builder.add("sfc", "*.astro")?;
+
+    builder.select("js").select("ts").select("sfc").build()
 }
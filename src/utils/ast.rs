@@ -16,6 +16,106 @@ pub fn is_jsx_attribute(item: &ast::JSXAttrOrSpread) -> Option<&ast::JSXAttr> {
     }
 }
 
+pub fn is_named_export_specifier(
+    specifier: &ast::ExportSpecifier,
+) -> Option<&ast::ExportNamedSpecifier> {
+    match specifier {
+        ast::ExportSpecifier::Named(specifier) => Some(specifier),
+        _ => None,
+    }
+}
+
 pub fn get_ident_name(ident: &swc_ecma_ast::Ident) -> String {
     ident.sym.to_string()
 }
+
+pub fn get_export_name(name: &ast::ModuleExportName) -> String {
+    match name {
+        ast::ModuleExportName::Ident(ident) => get_ident_name(ident),
+        ast::ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
+/// Get the imported name of a specifier. Use `default` for default imports or
+/// `*` for namespace imports.
+pub fn get_imported_name(specifier: &ast::ImportSpecifier) -> String {
+    match specifier {
+        ast::ImportSpecifier::Named(named) => named
+            .imported
+            .as_ref()
+            .map_or_else(|| get_ident_name(&named.local), get_export_name),
+        ast::ImportSpecifier::Default(_) => String::from("default"),
+        ast::ImportSpecifier::Namespace(_) => String::from("*"),
+    }
+}
+
+pub fn get_lit_value(lit: &ast::Lit) -> String {
+    match lit {
+        ast::Lit::Str(str) => str.value.to_string(),
+        ast::Lit::Bool(bool) => bool.value.to_string(),
+        ast::Lit::Num(num) => num.value.to_string(),
+        ast::Lit::BigInt(bigint) => bigint.value.to_string(),
+        ast::Lit::Regex(regex) => regex.exp.to_string(),
+        ast::Lit::JSXText(text) => text.value.to_string(),
+        ast::Lit::Null(_) => String::from("null"),
+    }
+}
+
+/// Get the dotted path of an expression such as `a.b.c`. Computed members are
+/// only supported when the key is a string literal (e.g., `a["b"]`).
+pub fn get_expr_path(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Ident(ident) => Some(get_ident_name(ident)),
+        ast::Expr::This(_) => Some(String::from("this")),
+        ast::Expr::MetaProp(meta) => match meta.kind {
+            ast::MetaPropKind::ImportMeta => Some(String::from("import.meta")),
+            ast::MetaPropKind::NewTarget => Some(String::from("new.target")),
+        },
+        ast::Expr::Member(member) => get_member_path(member),
+        ast::Expr::OptChain(chain) => match &*chain.base {
+            ast::OptChainBase::Member(member) => get_member_path(member),
+            ast::OptChainBase::Call(_) => None,
+        },
+        ast::Expr::Paren(paren) => get_expr_path(&paren.expr),
+        ast::Expr::TsNonNull(expr) => get_expr_path(&expr.expr),
+        _ => None,
+    }
+}
+
+pub fn get_member_path(member: &ast::MemberExpr) -> Option<String> {
+    let prop = match &member.prop {
+        ast::MemberProp::Ident(ident) => get_ident_name(ident),
+        ast::MemberProp::Computed(computed) => match &*computed.expr {
+            ast::Expr::Lit(ast::Lit::Str(str)) => str.value.to_string(),
+            _ => return None,
+        },
+        ast::MemberProp::PrivateName(name) => format!("#{}", name.id.sym),
+    };
+
+    Some(format!("{}.{}", get_expr_path(&member.obj)?, prop))
+}
+
+/// Get all identifiers bound by a pattern, including those nested in array and
+/// object destructuring.
+pub fn get_pat_idents(pat: &ast::Pat) -> Vec<&ast::Ident> {
+    match pat {
+        ast::Pat::Ident(ident) => vec![&ident.id],
+        ast::Pat::Array(array) => {
+            array.elems.iter().flatten().flat_map(get_pat_idents).collect()
+        }
+        ast::Pat::Rest(rest) => get_pat_idents(&rest.arg),
+        ast::Pat::Object(object) => object
+            .props
+            .iter()
+            .flat_map(|prop| match prop {
+                ast::ObjectPatProp::KeyValue(prop) => {
+                    get_pat_idents(&prop.value)
+                }
+                ast::ObjectPatProp::Assign(prop) => vec![&prop.key],
+                ast::ObjectPatProp::Rest(rest) => get_pat_idents(&rest.arg),
+            })
+            .collect(),
+        ast::Pat::Assign(assign) => get_pat_idents(&assign.left),
+        ast::Pat::Invalid(_) | ast::Pat::Expr(_) => vec![],
+    }
+}
@@ -1,16 +1,23 @@
 pub mod ast;
 
+use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use swc_common::{FileName, SourceFile};
 
+/// Get the absolute path of a source file to log. Files which were deleted,
+/// such as in watch mode, can't be canonicalized and are only absolutized.
 pub fn absolute_path(file: Rc<SourceFile>) -> String {
     file.unmapped_path
         .as_ref()
         .and_then(|f| match f {
-            FileName::Real(path) => {
-                Some(path.canonicalize().ok()?.to_str()?.to_string())
-            }
+            FileName::Real(path) => Some(
+                path.canonicalize()
+                    .unwrap_or_else(|_| absolutize(path))
+                    .to_str()?
+                    .to_string(),
+            ),
             _ => None,
         })
         .unwrap_or_else(|| file.name.to_string())
@@ -30,3 +37,59 @@ pub fn parse_key_value(
         None => (None, None),
     }
 }
+
+/// Normalize a path by removing `.` and resolving `..` components without
+/// accessing the file system.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Get the absolute and normalized form of a path. Relative paths are
+/// resolved from the current directory.
+pub fn absolutize(path: &Path) -> PathBuf {
+    match std::env::current_dir() {
+        Ok(dir) => normalize_path(&dir.join(path)),
+        Err(_) => normalize_path(path),
+    }
+}
+
+/// Make glob patterns relative to a directory match absolute paths instead
+pub fn absolute_globs(dir: &Path, patterns: &[String]) -> Vec<String> {
+    let dir = globset::escape(&absolutize(dir).to_string_lossy());
+
+    patterns
+        .iter()
+        .map(|pattern| {
+            normalize_path(&Path::new(&dir).join(pattern))
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Build a glob set matching any of the given patterns
+pub fn build_glob_set(patterns: &[String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+
+    builder.build()
+}
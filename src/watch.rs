@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use console::{style, Term};
+use swc_common::Loc;
+
+use crate::edit::Edit;
+use crate::logger::{
+    BufferLogger, JsonLogger, Logger, OutputFormat, QuickfixLogger,
+};
+use crate::parser::ParsedModule;
+use crate::processor::{
+    self, Match, ParseCache, Processor, ProcessorOptions, ProcessorRequest,
+};
+
+/// How often files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The modification time of each file found for a request
+type Snapshot = BTreeMap<PathBuf, Option<SystemTime>>;
+
+/// A match identified by its file, line, column and text, so matches from
+/// different runs can be compared.
+type MatchKey = (String, usize, usize, String);
+
+/// Run a request whenever files change until the process is stopped. Files
+/// are polled for changes rather than watched with OS notifications. Only the
+/// changed files are parsed and analyzed again, and requests using the module
+/// graph update it by replacing the changed modules.
+pub fn watch<R>(
+    request: R,
+    options: &ProcessorOptions,
+    format: OutputFormat,
+) -> !
+where
+    R: ProcessorRequest,
+{
+    let mut cache = ParseCache::new();
+    let mut output = WatchOutput::new(format);
+    let mut incremental = Incremental::new(request);
+    let mut previous = None;
+
+    loop {
+        let snapshot = take_snapshot(&incremental.request, options);
+
+        if previous.as_ref() != Some(&snapshot) {
+            cache.retain(|path, _| snapshot.contains_key(path));
+            incremental.update(&previous.take().unwrap_or_default(), &snapshot);
+            output.start();
+
+            let mut logger = BufferLogger::default();
+            Processor::new(&mut incremental, &mut logger, options)
+                .with_cache(&mut cache)
+                .process();
+
+            output.render(
+                logger
+                    .matches
+                    .into_iter()
+                    .map(|(text, loc, _)| (text, loc))
+                    .collect(),
+            );
+            previous = Some(snapshot);
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Keeps a request and the matches of each module between runs, so unchanged
+/// modules replay their matches instead of being analyzed again.
+struct Incremental<R: ProcessorRequest> {
+    request: R,
+    matches: HashMap<PathBuf, Vec<Match>>,
+    /// The files which changed since the previous run
+    changed: HashSet<PathBuf>,
+}
+
+impl<R: ProcessorRequest> Incremental<R> {
+    fn new(request: R) -> Incremental<R> {
+        Incremental {
+            request,
+            matches: HashMap::new(),
+            changed: HashSet::new(),
+        }
+    }
+
+    /// Find the files which were added, modified or deleted since the previous
+    /// snapshot, and make the request forget their modules.
+    fn update(&mut self, previous: &Snapshot, snapshot: &Snapshot) {
+        self.changed = snapshot
+            .iter()
+            .filter(|(path, modified)| previous.get(*path) != Some(modified))
+            .map(|(path, _)| path.clone())
+            .chain(
+                previous
+                    .keys()
+                    .filter(|path| !snapshot.contains_key(*path))
+                    .cloned(),
+            )
+            .collect();
+
+        for path in &self.changed {
+            self.matches.remove(path);
+            self.request.forget(path);
+        }
+    }
+}
+
+impl<R: ProcessorRequest> ProcessorRequest for &mut Incremental<R> {
+    fn path(&self) -> &PathBuf {
+        self.request.path()
+    }
+
+    fn uses_all_modules(&self) -> bool {
+        self.request.uses_all_modules()
+    }
+
+    fn analyze(&mut self, parsed: &ParsedModule) -> Vec<Match> {
+        if let Some(matches) = self.matches.get(&parsed.path) {
+            return matches.clone();
+        }
+
+        let matches = self.request.analyze(parsed);
+        self.matches.insert(parsed.path.clone(), matches.clone());
+        matches
+    }
+
+    fn edit(&mut self, parsed: &ParsedModule) -> Vec<Edit> {
+        // Unchanged modules were already edited in a previous run
+        match self.changed.contains(&parsed.path) {
+            true => self.request.edit(parsed),
+            false => vec![],
+        }
+    }
+
+    fn finish(&mut self) -> Vec<(String, Loc)> {
+        self.request.finish()
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.request.forget(path)
+    }
+
+    fn errors(&mut self) -> Vec<String> {
+        self.request.errors()
+    }
+}
+
+fn take_snapshot<R: ProcessorRequest>(
+    request: &R,
+    options: &ProcessorOptions,
+) -> Snapshot {
+    processor::find_files(request, options)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+
+            (path, modified)
+        })
+        .collect()
+}
+
+/// Prints the matches of each run. The console formats redraw the full list
+/// of matches, while JSON emits events for the matches added or removed
+/// since the previous run.
+struct WatchOutput {
+    format: OutputFormat,
+    previous: BTreeMap<MatchKey, (String, Loc)>,
+}
+
+impl WatchOutput {
+    fn new(format: OutputFormat) -> WatchOutput {
+        WatchOutput { format, previous: BTreeMap::new() }
+    }
+
+    /// Prepare for a run. The screen is cleared before running so errors
+    /// printed while processing stay visible.
+    fn start(&self) {
+        let term = Term::stdout();
+
+        if term.is_term() && !matches!(self.format, OutputFormat::Json) {
+            let _ = term.clear_screen();
+        }
+    }
+
+    fn render(&mut self, matches: Vec<(String, Loc)>) {
+        match self.format {
+            OutputFormat::Default => render_grouped(&matches),
+            OutputFormat::Quickfix => {
+                let mut logger = QuickfixLogger::new();
+
+                for (text, loc) in matches {
+                    logger.log(text, loc);
+                }
+            }
+            OutputFormat::Json => self.render_events(matches),
+        }
+    }
+
+    fn render_events(&mut self, matches: Vec<(String, Loc)>) {
+        let mut logger = JsonLogger::new();
+        let current: BTreeMap<MatchKey, (String, Loc)> = matches
+            .into_iter()
+            .map(|(text, loc)| {
+                let key = (
+                    loc.file.name.to_string(),
+                    loc.line,
+                    loc.col.0,
+                    text.clone(),
+                );
+
+                (key, (text, loc))
+            })
+            .collect();
+
+        for (key, (text, loc)) in &self.previous {
+            if !current.contains_key(key) {
+                logger.log_event("removed", text.clone(), loc.clone());
+            }
+        }
+
+        for (key, (text, loc)) in &current {
+            if !self.previous.contains_key(key) {
+                logger.log_event("added", text.clone(), loc.clone());
+            }
+        }
+
+        self.previous = current;
+    }
+}
+
+/// Print matches grouped by their file, followed by a summary
+fn render_grouped(matches: &[(String, Loc)]) {
+    let mut files: Vec<(String, Vec<&(String, Loc)>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for m in matches {
+        let file = m.1.file.name.to_string();
+        let position = *index.entry(file.clone()).or_insert_with(|| {
+            files.push((file, Vec::new()));
+            files.len() - 1
+        });
+
+        files[position].1.push(m);
+    }
+
+    for (file, matches) in &files {
+        println!("{}", style(file).bold());
+
+        for (text, loc) in matches {
+            println!("  {}:{} {}", loc.line, loc.col.0 + 1, style(text).cyan());
+        }
+
+        println!();
+    }
+
+    println!(
+        "{}",
+        style(format!(
+            "{} matches in {} files. Watching for changes...",
+            matches.len(),
+            files.len()
+        ))
+        .dim()
+    );
+}
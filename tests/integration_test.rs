@@ -354,7 +354,7 @@ fn ignores_unsupported_file_types() -> Result<(), Box<dyn std::error::Error>> {
 
     let foo = dir.child("foo.js");
     foo.write_str(content)?;
-    let bar = dir.child("bar.vue");
+    let bar = dir.child("bar.html");
     bar.write_str(content)?;
     let bar = dir.child("baz.txt");
     bar.write_str(content)?;
@@ -370,6 +370,126 @@ fn ignores_unsupported_file_types() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn searches_component_scripts() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.vue").write_str(concat!(
+        "<template>\n",
+        "  <div>{{ a < b }}</div>\n",
+        "</template>\n",
+        "<script setup lang=\"ts\">\n",
+        "import { ref } from 'vue';\n",
+        "const count = ref<number>(0);\n",
+        "</script>\n",
+    ))?;
+    dir.child("b.svelte").write_str(concat!(
+        "<script context=\"module\">\n",
+        "  import { writable } from 'svelte/store';\n",
+        "</script>\n",
+        "<h1>{title}</h1>\n",
+    ))?;
+    dir.child("d.vue").write_str(concat!(
+        "<script setup lang=\"ts\" ",
+        "generic=\"T extends Item<string>, U extends string\">\n",
+        "import { computed } from 'vue';\n",
+        "</script>\n",
+    ))?;
+    dir.child("c.astro").write_str(concat!(
+        "---\n",
+        "import { ref } from 'vue';\n",
+        "const title: string = 'Hi';\n",
+        "---\n",
+        "<h1>{title}</h1>\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["imports", "vue", "a.vue", "c.astro", "d.vue"]);
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n",
+        "./a.vue:5:1 import { ref } from 'vue';",
+        "./c.astro:2:1 import { ref } from 'vue';",
+        "./d.vue:2:1 import { computed } from 'vue';",
+    )));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["imports", "svelte/store"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./b.svelte:2:3 import { writable } from 'svelte/store';\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["rewrite-imports", "vue", "vue-demi", "a.vue"]);
+    cmd.assert().success();
+
+    dir.child("a.vue").assert(predicate::str::contains(
+        "<script setup lang=\"ts\">\nimport { ref } from 'vue-demi';\n",
+    ));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn exits_with_one_without_matches() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts").write_str("import { useState } from 'react'")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("imports").arg("vue");
+    cmd.assert().code(1).stdout("");
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn fail_on_match() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts").write_str("import { useState } from 'react'")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--fail-on-match", "imports", "react"]);
+    cmd.assert()
+        .code(1)
+        .stdout("./a.ts:1:1 import { useState } from 'react'\n");
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--fail-on-match", "imports", "vue"]);
+    cmd.assert().success().stdout("");
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn exits_with_two_on_errors() -> Result<(), Box<dyn std::error::Error>> {
+    // Other tests search the system temp directory, so the invalid file is
+    // kept out of it.
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("errors");
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("a.ts"), "import { useState } from 'react'")?;
+    std::fs::write(dir.join("b.ts"), "import {")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(&dir);
+    cmd.args(["--format", "json", "imports", "react"]);
+    cmd.assert()
+        .code(2)
+        .stdout(predicate::str::contains("\"line\": 1"))
+        .stderr(predicate::str::contains(
+            "Encountered parsing error while reading ./b.ts:1:8",
+        ));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
 #[test]
 fn supports_decorators() -> Result<(), Box<dyn std::error::Error>> {
     let dir = assert_fs::TempDir::new().unwrap();
@@ -425,3 +545,1659 @@ fn supports_import_assertions() -> Result<(), Box<dyn std::error::Error>> {
     dir.close().unwrap();
     Ok(())
 }
+
+#[test]
+fn calls() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("calls.tsx")?;
+    file.write_str(
+        "
+        useEffect(() => {}, [])
+        useMemo(() => {}, [])
+        const x = useEffect(fn)
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("calls").arg("useEffect");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./calls.tsx:2:9 useEffect(() => {}, [])",
+        "./calls.tsx:4:19 const x = useEffect(fn)",
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn member_calls() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("calls.tsx")?;
+    file.write_str(
+        "
+        analytics.track('Signup')
+        window.analytics.track('Signup')
+        analytics?.track('Login')
+        analytics['track']('Logout')
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("calls").arg("analytics.track");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n",
+        "./calls.tsx:2:9 analytics.track('Signup')",
+        "./calls.tsx:4:9 analytics?.track('Login')",
+        "./calls.tsx:5:9 analytics['track']('Logout')",
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn calls_by_args() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("calls.tsx")?;
+    file.write_str(
+        "
+        track('Signup', { plan: 'pro' })
+        track('Login')
+        track(`Signup`)
+        track(event)
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("calls").arg("track").arg("Signup");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./calls.tsx:2:9 track('Signup', { plan: 'pro' })",
+        "./calls.tsx:4:9 track(`Signup`)",
+    )));
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("calls").arg("track").arg("--arg-count").arg("1").arg("_");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n",
+        "./calls.tsx:3:9 track('Login')",
+        "./calls.tsx:4:9 track(`Signup`)",
+        "./calls.tsx:5:9 track(event)",
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn calls_by_source() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("calls.tsx")?;
+    file.write_str(
+        "
+        import { useEffect as useReactEffect } from 'react'
+        import * as React from 'react'
+        import { useEffect } from './hooks'
+
+        useReactEffect(() => {})
+        React.useEffect(() => {})
+        useEffect(() => {})
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("calls").arg("useEffect").arg("--source").arg("react");
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./calls.tsx:6:9 useReactEffect(() => {})\n",
+    ));
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("calls").arg("React.useEffect").arg("--source").arg("react");
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./calls.tsx:7:9 React.useEffect(() => {})\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn references() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("references.tsx")?;
+    file.write_str(
+        "
+        import { useState as useLocalState } from 'react'
+        const [count, setCount] = useLocalState(0)
+        const ui = <Counter init={useLocalState} />
+        useState(1)
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("references").arg("react").arg("useState");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./references.tsx:3:35 const [count, setCount] = useLocalState(0)",
+        "./references.tsx:4:35 const ui = <Counter init={useLocalState} />",
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn references_with_shadowing() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("references.tsx")?;
+    file.write_str(
+        "
+        import { useState } from 'react'
+        function a(useState) { useState() }
+        function b() { if (x) { var useState } useState() }
+        function c() { { let useState } useState() }
+        try {} catch (useState) { useState() }
+        for (const useState of []) { useState() }
+        const d = { useState }
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("references").arg("react").arg("useState");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./references.tsx:5:41 function c() { { let useState } useState() }",
+        "./references.tsx:8:21 const d = { useState }",
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn references_to_default_import() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("references.tsx")?;
+    file.write_str(
+        "
+        import React from 'react'
+        const ui = <React.Fragment />
+        React.useState()
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("references").arg("react").arg("default");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./references.tsx:3:21 const ui = <React.Fragment />",
+        "./references.tsx:4:9 React.useState()",
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn members() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("members.ts")?;
+    file.write_str(
+        "
+        const url = process.env.API_URL
+        const key = process.env['API_KEY']
+        const env = process.env
+        const mode = import.meta.env.MODE
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("members").arg("process.env.*");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./members.ts:2:21 const url = process.env.API_URL",
+        "./members.ts:3:21 const key = process.env['API_KEY']",
+    )));
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("members").arg("import.meta.env.*");
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./members.ts:5:22 const mode = import.meta.env.MODE\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn unique_members() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("members.ts")?;
+    file.write_str(
+        "
+        window.localStorage.getItem('a')
+        window.sessionStorage.getItem('b')
+        window.localStorage.setItem('a', 'c')
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("members").arg("window.*").arg("--unique");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./members.ts:2:9 window.localStorage",
+        "./members.ts:3:9 window.sessionStorage",
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn exports() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("exports.ts")?;
+    file.write_str(
+        "
+        export const a = 1
+        export function b() {}
+        export type C = string
+        const d = 2
+        export { d as e }
+        export default d
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("exports");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n{}\n{}\n",
+        "./exports.ts:2:9 a",
+        "./exports.ts:3:9 b",
+        "./exports.ts:4:9 type C",
+        "./exports.ts:6:9 e -> d",
+        "./exports.ts:7:9 default -> d",
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn re_exports() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("exports.ts")?;
+    file.write_str(
+        "
+        export * from './a'
+        export * as b from './b'
+        export { c as d, type E } from './c'
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("exports");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n{}\n",
+        "./exports.ts:2:9 * from \"./a\"",
+        "./exports.ts:3:9 b -> * from \"./b\"",
+        "./exports.ts:4:9 d -> c from \"./c\"",
+        "./exports.ts:4:9 type E -> E from \"./c\"",
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn export_assignment() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("exports.ts")?;
+    file.write_str(
+        "
+        const config = {}
+        export = config
+        ",
+    )?;
+
+    let mut cmd = create_cmd(&file)?;
+    cmd.arg("--format").arg("json").arg("exports");
+    cmd.assert().success().stdout(
+        predicate::str::is_match(
+            json("exports.ts", 3, 9, "= -> config").as_str(),
+        )
+        .unwrap(),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unused_exports() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts").write_str(
+        "
+        import { b } from './b'
+        import * as c from './c'
+        ",
+    )?;
+    dir.child("b.ts").write_str(
+        "
+        export const b = 1
+        export const unused = 2
+        ",
+    )?;
+    dir.child("c.ts").write_str("export const c = 1")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("unused-exports");
+    cmd.assert().success().stdout(predicate::str::diff("./b.ts:3:9 unused\n"));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn unused_exports_through_barrels() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("index.ts").write_str(
+        "
+        export { Button } from './components'
+        export const version = 1
+        ",
+    )?;
+    dir.child("components/index.ts").write_str(
+        "
+        export * from './button'
+        export { Input } from './input'
+        ",
+    )?;
+    dir.child("components/button.tsx").write_str(
+        "
+        export const Button = () => null
+        export const ButtonGroup = () => null
+        ",
+    )?;
+    dir.child("components/input.tsx").write_str(
+        "
+        export const Input = () => null
+        ",
+    )?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("unused-exports").arg("--entry").arg("index.ts");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n",
+        "./components/button.tsx:3:9 ButtonGroup",
+        "./components/index.ts:3:9 Input -> Input from \"./input\"",
+        "./components/input.tsx:2:9 Input",
+    )));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn check_boundaries() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("flashlight.toml").write_str(
+        r#"
+# Features can use the shared API client
+[[boundaries]]
+from = "src/features/**"
+to = "src/app/api/**"
+allow = true
+
+[[boundaries]]
+from = "src/features/**"
+to = "src/app/**"
+message = "Features may not depend on the app"
+
+[[boundaries]]
+from = "**"
+to = "packages/*/src/**"
+cross_package = true
+"#,
+    )?;
+    dir.child("src/app/index.ts").write_str("export const a = 1")?;
+    dir.child("src/app/api/client.ts").write_str("export const b = 1")?;
+    dir.child("src/features/a.ts").write_str(
+        "import { a } from '../app'\nimport { b } from '../app/api/client'",
+    )?;
+    dir.child("src/features/b.ts").write_str(
+        "import { c } from '@scope/ui/src/c'\nimport { d } from '@scope/ui'",
+    )?;
+    dir.child("packages/ui/package.json")
+        .write_str(r#"{ "name": "@scope/ui" }"#)?;
+    dir.child("packages/ui/index.ts")
+        .write_str("import { c } from './src/c'")?;
+    dir.child("packages/ui/src/c.ts").write_str("export const c = 1")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("check");
+    cmd.assert().code(1).stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./src/features/a.ts:1:1 error: Features may not depend on the app: import { a } from '../app'",
+        "./src/features/b.ts:1:1 error: src/features/b.ts may not import packages/ui/src/c.ts: import { c } from '@scope/ui/src/c'",
+    )));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn check_without_violations() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("flashlight.toml")
+        .write_str("[[boundaries]]\nfrom = \"src/**\"\nto = \"test/**\"\n")?;
+    dir.child("src/a.ts").write_str("import { b } from './b'")?;
+    dir.child("src/b.ts").write_str("export const b = 1")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("check");
+    cmd.assert().success().stdout("");
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn check_rules() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("flashlight.toml").write_str(
+        r#"
+[[rules]]
+source = "moment"
+message = "Use date-fns"
+
+[[rules]]
+source = "lodash"
+specifier = "default"
+message = "Import lodash functions individually"
+
+[[rules]]
+source = "@mui/material"
+severity = "warning"
+message = "Import components from their own module"
+"#,
+    )?;
+    dir.child("a.ts").write_str(
+        r#"import moment from 'moment'
+import _ from 'lodash'
+import { get } from 'lodash'
+import { Button } from '@mui/material'
+import Card from '@mui/material/Card'
+const m = require('moment')
+"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("check");
+    cmd.assert().code(1).stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n{}\n",
+        "./a.ts:1:1 error: Use date-fns: import moment from 'moment'",
+        "./a.ts:2:1 error: Import lodash functions individually: import _ from 'lodash'",
+        "./a.ts:4:1 warning: Import components from their own module: import { Button } from '@mui/material'",
+        "./a.ts:6:11 error: Use date-fns: const m = require('moment')",
+    )));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn check_warnings() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("flashlight.toml").write_str(
+        "[[rules]]\nsource = \"moment\"\nkind = \"static\"\nseverity = \"warning\"\nmessage = \"Use date-fns\"\n",
+    )?;
+    dir.child("a.ts")
+        .write_str("import moment from 'moment'\nimport('moment')")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("check");
+    cmd.assert().success().stdout(
+        "./a.ts:1:1 warning: Use date-fns: import moment from 'moment'\n",
+    );
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn baseline() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let file = dir.child("src/a.ts");
+    file.write_str("import moment from 'moment'\nimport { a } from 'moment'")?;
+
+    let run = |args: &[&str]| -> Result<Command, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("flashlight")?;
+        cmd.current_dir(dir.path());
+        cmd.args(["--baseline", "baseline.json"]);
+        cmd.args(args);
+        cmd.args(["imports", "moment"]);
+        Ok(cmd)
+    };
+
+    // The baseline is created on the first run
+    run(&[])?.assert().success().stdout("");
+    dir.child("baseline.json").assert(predicate::str::diff(
+        r#"{
+  "src/a.ts": {
+    "import moment from 'moment'": 1,
+    "import { a } from 'moment'": 1
+  }
+}
+"#,
+    ));
+
+    // Only matches exceeding the baseline are reported
+    file.write_str(
+        "import moment from 'moment'\nimport { a } from 'moment'\nimport moment from 'moment'",
+    )?;
+    run(&[])?
+        .assert()
+        .code(1)
+        .stdout("./src/a.ts:3:1 import moment from 'moment'\n");
+
+    // Removed matches are only written to the baseline when requested
+    file.write_str("import moment from 'moment'")?;
+    run(&[])?.assert().success().stdout("");
+    run(&["--update-baseline"])?.assert().success().stdout("");
+    dir.child("baseline.json").assert(predicate::str::diff(
+        "{\n  \"src/a.ts\": {\n    \"import moment from 'moment'\": 1\n  }\n}\n",
+    ));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn cycles() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts").write_str("import { b } from './b'")?;
+    dir.child("b.ts").write_str("import { c } from './c.js'")?;
+    dir.child("c.ts").write_str("import { a } from './a'")?;
+    dir.child("d.ts").write_str("import { a } from './a'")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("cycles");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n",
+        "./a.ts:1:1 cycle 1: import { b } from './b'",
+        "./b.ts:1:1 cycle 1: import { c } from './c.js'",
+        "./c.ts:1:1 cycle 1: import { a } from './a'",
+    )));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn cycles_ignoring_type_imports() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts").write_str("import { b } from './b'")?;
+    dir.child("b.ts").write_str("import type { A } from './a'")?;
+    dir.child("c.ts").write_str("import { d } from './d'")?;
+    dir.child("d.ts").write_str("import { type C, c } from './c'")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("cycles").arg("--ignore-type-imports");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./c.ts:1:1 cycle 1: import { d } from './d'",
+        "./d.ts:1:1 cycle 1: import { type C, c } from './c'",
+    )));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn deps() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("package.json").write_str(
+        r#"{
+  "name": "app",
+  "dependencies": {
+    "@scope/pkg": "1.0.0",
+    "left-pad": "1.0.0",
+    "lodash": "1.0.0"
+  },
+  "devDependencies": {
+    "jest": "1.0.0",
+    "@types/react": "1.0.0"
+  }
+}"#,
+    )?;
+    dir.child("a.ts").write_str(
+        r#"import get from 'lodash/get'
+import { x } from '@scope/pkg/x'
+import fs from 'node:fs'
+import path from 'path'
+import type { FC } from 'react'
+import { b } from './b'
+const chalk = require('chalk')
+"#,
+    )?;
+    dir.child("b.ts").write_str("import { expect } from 'jest'")?;
+    dir.child("b.test.ts").write_str("import { test } from 'jest'")?;
+    dir.child("c.ts").write_str("import { b } from '@/b'")?;
+    dir.child("flashlight.toml").write_str("tsconfig = \"tsconfig.json\"")?;
+    dir.child("tsconfig.json").write_str(
+        r#"{ "compilerOptions": { "paths": { "@/*": ["./*"] } } }"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["deps", "--dev", "*.test.ts"]);
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n",
+        "./a.ts:7:15 missing dependency \"chalk\": const chalk = require('chalk')",
+        "./b.ts:1:1 dev dependency \"jest\": import { expect } from 'jest'",
+        "./package.json:5:5 unused dependency \"left-pad\"",
+    )));
+
+    // Packages with an invalid manifest aren't audited
+    dir.child("pkg/package.json").write_str("{ \"name\": ")?;
+    dir.child("pkg/x.ts").write_str("import x from 'x'")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["deps", "--dev", "*.test.ts"]);
+    cmd.assert()
+        .code(2)
+        .stdout(predicate::str::contains("\"x\"").not())
+        .stderr(predicate::str::contains("Invalid manifest "))
+        .stderr(predicate::str::contains("pkg/package.json: EOF"));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn graph() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("index.ts").write_str("import { a } from './lib/a'")?;
+    dir.child("lib/a.ts").write_str("import { b } from './b'")?;
+    dir.child("lib/b.ts").write_str("import React from 'react'")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("graph");
+    cmd.assert().success().stdout(predicate::str::diff(
+        "digraph {
+  \"index.ts\" -> \"lib/a.ts\";
+  \"lib/a.ts\" -> \"lib/b.ts\";
+  \"lib/b.ts\";
+}
+",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("graph").arg("--collapse").arg("directory");
+    cmd.arg("--diagram").arg("mermaid");
+    cmd.assert().success().stdout(predicate::str::diff(
+        "graph LR
+  n0[\".\"]
+  n1[\"lib\"]
+  n0 --> n1
+",
+    ));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn graph_from_root() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("index.ts").write_str("import { a } from './a'")?;
+    dir.child("a.ts").write_str("import { b } from './b'")?;
+    dir.child("b.ts").write_str("export const b = 1")?;
+    dir.child("c.ts").write_str("import { a } from './a'")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("--format").arg("json").arg("graph");
+    cmd.arg("--root").arg("index.ts").arg("--depth").arg("1");
+    cmd.assert().success().stdout(predicate::str::diff(
+        "{
+  \"a.ts\": [],
+  \"index.ts\": [
+    \"a.ts\"
+  ]
+}
+",
+    ));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn search() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.tsx").write_str(concat!(
+        "useEffect(() => {\n",
+        "  save();\n",
+        "}, []);\n",
+        "useEffect(( ) => save(), [ ]);\n",
+        "useEffect(() => save(), [id]);\n",
+        "const ids = list.map((x) => x.id).filter(Boolean);\n",
+        "log('a', 1, 2);\n",
+        "<Button onClick={save} variant='primary' disabled>Save</Button>;\n",
+        "<Button variant='secondary' />;\n",
+        "export function load() {}\n",
+        "for (let i = 0; i < 3; i++) {}\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "useEffect($CB, [])"]);
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./a.tsx:1:1 useEffect(() => { [$CB=() => { save(); }]",
+        "./a.tsx:4:1 useEffect(( ) => save(), [ ]); [$CB=( ) => save()]",
+    )));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "$A.map($F).filter(Boolean)"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./a.tsx:6:13 const ids = list.map((x) => x.id).filter(Boolean); \
+         [$A=list, $F=(x) => x.id]\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "log($_, $$$REST)"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./a.tsx:7:1 log('a', 1, 2); [$REST=1, 2]\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "<Button variant=$V onClick={$F} />"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./a.tsx:8:1 <Button onClick={save} variant='primary' disabled>Save</Button>; \
+         [$V='primary', $F=save]\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "function $F() {}"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./a.tsx:10:8 export function load() {} [$F=load]\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "let $I = 0"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./a.tsx:11:6 for (let i = 0; i < 3; i++) {} [$I=i]\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "useEffect("]);
+    cmd.assert().code(2).stderr(predicate::str::contains("Invalid pattern"));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn search_large_file() -> Result<(), Box<dyn std::error::Error>> {
+    // Each operand of a long chain of operators is nested in the previous
+    // operation, so the search must not copy each candidate's subtree
+    let terms: Vec<String> = (0..2000).map(|i| format!("a{}", i)).collect();
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts")
+        .write_str(&format!("const x = {};\n", terms.join(" + ")))?;
+
+    let mut cmd = assert_cmd::Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "$X"]);
+    cmd.timeout(std::time::Duration::from_secs(30));
+
+    let output = cmd.assert().success().get_output().stdout.clone();
+    assert_eq!(String::from_utf8(output)?.lines().count(), 3999);
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn replace() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.tsx").write_str(concat!(
+        "const ids = list.map((x) => x.id).filter(Boolean);\n",
+        "const other = list.map(getId);\n",
+        "<Button onClick={save} variant='primary' disabled>Save</Button>;\n",
+        "<Button variant='secondary' />;\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--dry-run", "replace", "$A.map($F).filter(Boolean)"]);
+    cmd.arg("$A.flatMap($F)");
+    cmd.assert().success().stdout(predicate::str::contains(concat!(
+        "-const ids = list.map((x) => x.id).filter(Boolean);\n",
+        "+const ids = list.flatMap((x) => x.id);\n",
+    )));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["replace", "$A.map($F).filter(Boolean)", "$A.flatMap($F)"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./a.tsx:1:13 const ids = list.map((x) => x.id).filter(Boolean);\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["replace", "<Button variant=$V $$$A>$$$C</Button>"]);
+    cmd.arg("<Button tone=$V $$$A>$$$C</Button>");
+    cmd.assert().success();
+
+    dir.child("a.tsx").assert(concat!(
+        "const ids = list.flatMap((x) => x.id);\n",
+        "const other = list.map(getId);\n",
+        "<Button tone='primary' onClick={save} disabled>Save</Button>;\n",
+        "<Button tone='secondary'></Button>;\n",
+    ));
+
+    // Parentheses are kept, and added where a pasted expression would
+    // otherwise change meaning
+    dir.child("b.ts").write_str(concat!(
+        "const a = (x || y).map(f).filter(Boolean);\n",
+        "const b = (await load()).map(g).filter(Boolean);\n",
+        "const c = add(x + y, x + y);\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["replace", "$A.map($F).filter(Boolean)", "$A.flatMap($F)"]);
+    cmd.arg("b.ts");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["replace", "add($X, $X)", "$X * 2", "b.ts"]);
+    cmd.assert().success();
+
+    dir.child("b.ts").assert(concat!(
+        "const a = (x || y).flatMap(f);\n",
+        "const b = (await load()).flatMap(g);\n",
+        "const c = (x + y) * 2;\n",
+    ));
+
+    // Matches nested in the code bound to metavariables are replaced too
+    dir.child("c.ts").write_str(concat!(
+        "foo(foo(1));\n",
+        "log(foo(bar(foo(2))), foo(3));\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["replace", "foo($X)", "baz($X)", "c.ts"]);
+    cmd.assert().success();
+
+    dir.child("c.ts")
+        .assert(concat!("baz(baz(1));\n", "log(baz(bar(baz(2))), baz(3));\n",));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["replace", "$A.map($F)", "$B.map($F)"]);
+    cmd.assert()
+        .code(2)
+        .stderr(predicate::str::contains("Unknown metavariable $B"));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn query() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("flashlight.toml")
+        .write_str("[queries]\nforms = [\"tags\", \"Form\"]\n")?;
+    dir.child("a.tsx").write_str(concat!(
+        "import { useQuery } from 'react-query';\n",
+        "import * as yup from 'yup';\n",
+        "<Form><QueryBoundary /></Form>;\n",
+    ))?;
+    dir.child("b.tsx").write_str(concat!(
+        "import { useQuery } from 'react-query';\n",
+        "<Form />;\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["query", "[imports react-query] and not [tags QueryBoundary]"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./b.tsx:1:1 import { useQuery } from 'react-query';\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["query", "forms and ([imports yup] or [search 'yup.$_'])"]);
+    cmd.args(["--matches", "1"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./a.tsx:3:1 <Form><QueryBoundary /></Form>;\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["query", "not forms"]);
+    cmd.assert().code(1).stdout("");
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["query", "[imports yup] and"]);
+    cmd.assert().code(2).stderr(predicate::str::contains("Invalid query"));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["query", "[imports yup] and not forms", "--matches", "2"]);
+    cmd.assert().code(2).stderr(predicate::str::contains("negated"));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn batch() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("queries.toml").write_str(concat!(
+        "forms = [\"tags\", \"Form\"]\n",
+        "react-query = [\"imports\", \"react-query\"]\n",
+        "unbounded = [\"query\", \"[imports react-query] and not [tags QueryBoundary]\"]\n",
+    ))?;
+    dir.child("a.tsx").write_str(concat!(
+        "import { useQuery } from 'react-query';\n",
+        "<Form />;\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["batch", "queries.toml"]);
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n",
+        "./a.tsx:2:1 [forms] <Form />;",
+        "./a.tsx:1:1 [react-query] import { useQuery } from 'react-query';",
+        "./a.tsx:1:1 [unbounded] import { useQuery } from 'react-query';",
+    )));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--format", "json", "batch", "queries.toml"]);
+    cmd.assert().success().stdout(predicate::str::starts_with(
+        "{\"query\": \"forms\", \"file\": ",
+    ));
+
+    dir.child("invalid.toml").write_str("graph = [\"graph\"]\n")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["batch", "invalid.toml"]);
+    cmd.assert().code(2).stderr(predicate::str::contains("query or batch"));
+
+    dir.child("empty.toml").write_str("# No queries yet\n")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["batch", "empty.toml"]);
+    cmd.assert().code(2).stderr(predicate::str::contains("No queries"));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn rewrite_imports() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts").write_str(concat!(
+        "import { Button, Card as C } from '@acme/old-ui';\n",
+        "export { Icon } from \"@acme/old-ui\";\n",
+        "const ui = require('@acme/old-ui');\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["rewrite-imports", "@acme/old-ui", "@acme/ui"]);
+    cmd.args(["--rename", "Button=BaseButton"]);
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n{}\n",
+        "./a.ts:1:10 import { Button, Card as C } from '@acme/old-ui';",
+        "./a.ts:1:35 import { Button, Card as C } from '@acme/old-ui';",
+        "./a.ts:2:22 export { Icon } from \"@acme/old-ui\";",
+        "./a.ts:3:20 const ui = require('@acme/old-ui');",
+    )));
+
+    dir.child("a.ts").assert(concat!(
+        "import { BaseButton as Button, Card as C } from '@acme/ui';\n",
+        "export { Icon } from \"@acme/ui\";\n",
+        "const ui = require('@acme/ui');\n",
+    ));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn rewrite_imports_specifiers() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts").write_str(concat!(
+        "import Old, { Button, Card } from \"@acme/old-ui\"\n",
+        "import { Theme } from \"@acme/ui\"\n",
+    ))?;
+    dir.child("b.ts").write_str(concat!(
+        "import { Button, Card } from '@acme/old-ui';\n",
+        "import { Link } from '@acme/old-ui';\n",
+    ))?;
+    dir.child("c.ts").write_str(concat!(
+        "import Old, { Button } from '@acme/old-ui'\n",
+        "import {\n",
+        "  Card,\n",
+        "  Theme\n",
+        "} from '@acme/ui'\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["rewrite-imports", "@acme/old-ui", "@acme/ui"]);
+    cmd.args(["a.ts", "b.ts", "c.ts"]);
+    cmd.args(["--specifier", "Button", "--specifier", "default"]);
+    cmd.assert().success();
+
+    dir.child("a.ts").assert(concat!(
+        "import { Card } from \"@acme/old-ui\"\n",
+        "import Old, { Theme, Button } from \"@acme/ui\"\n",
+    ));
+    dir.child("b.ts").assert(concat!(
+        "import { Card } from '@acme/old-ui';\n",
+        "import { Button } from '@acme/ui';\n",
+        "import { Link } from '@acme/old-ui';\n",
+    ));
+    dir.child("c.ts").assert(concat!(
+        "import Old, {\n",
+        "  Card,\n",
+        "  Theme,\n",
+        "  Button\n",
+        "} from '@acme/ui'\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["rewrite-imports", "@acme/ui", "@acme/ui", "b.ts"]);
+    cmd.args(["--rename", "Button=BaseButton"]);
+    cmd.assert().success();
+
+    dir.child("b.ts").assert(concat!(
+        "import { Card } from '@acme/old-ui';\n",
+        "import { BaseButton as Button } from '@acme/ui';\n",
+        "import { Link } from '@acme/old-ui';\n",
+    ));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn rewrite_tags() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.tsx").write_str(concat!(
+        "import { Button, Card } from '@acme/ui';\n",
+        "\n",
+        "export const App = () => (\n",
+        "  <Card>\n",
+        "    <Button variant=\"primary\" color='red'>Save</Button>\n",
+        "    <Button variant=\"secondary\" legacy />\n",
+        "  </Card>\n",
+        ");\n",
+    ))?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["rewrite-tags", "Button", "variant=primary"]);
+    cmd.args(["--rename", "BaseButton", "--set", "variant=solid"]);
+    cmd.args(["--rename-attribute", "color=tone", "--set", "type=submit"]);
+    cmd.assert().success();
+
+    dir.child("a.tsx").assert(concat!(
+        "import { Button, BaseButton, Card } from '@acme/ui';\n",
+        "\n",
+        "export const App = () => (\n",
+        "  <Card>\n",
+        "    <BaseButton variant=\"solid\" tone='red' type=\"submit\">Save</BaseButton>\n",
+        "    <Button variant=\"secondary\" legacy />\n",
+        "  </Card>\n",
+        ");\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["rewrite-tags", "Button", "--rename", "BaseButton"]);
+    cmd.args(["--remove", "legacy", "--set", "disabled"]);
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n{}\n{}\n",
+        "./a.tsx:1:10 import { Button, BaseButton, Card } from '@acme/ui';",
+        "./a.tsx:6:6 <Button variant=\"secondary\" legacy />",
+        "./a.tsx:6:32 <Button variant=\"secondary\" legacy />",
+        "./a.tsx:6:39 <Button variant=\"secondary\" legacy />",
+    )));
+
+    dir.child("a.tsx").assert(concat!(
+        "import { BaseButton, Card } from '@acme/ui';\n",
+        "\n",
+        "export const App = () => (\n",
+        "  <Card>\n",
+        "    <BaseButton variant=\"solid\" tone='red' type=\"submit\">Save</BaseButton>\n",
+        "    <BaseButton variant=\"secondary\" disabled />\n",
+        "  </Card>\n",
+        ");\n",
+    ));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn rewrite_tags_spread() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.tsx").write_str(concat!(
+        "export const App = (props) => (\n",
+        "  <Card {...props}>\n",
+        "    <Button {...props} />\n",
+        "    <Button\n",
+        "      {...props}\n",
+        "    >\n",
+        "      Save\n",
+        "    </Button>\n",
+        "  </Card>\n",
+        ");\n",
+    ))?;
+
+    // New attributes are added after spread attributes so they take precedence
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["rewrite-tags", "Button", "--set", "variant=solid"]);
+    cmd.assert().success();
+
+    dir.child("a.tsx").assert(concat!(
+        "export const App = (props) => (\n",
+        "  <Card {...props}>\n",
+        "    <Button {...props} variant=\"solid\" />\n",
+        "    <Button\n",
+        "      {...props} variant=\"solid\"\n",
+        "    >\n",
+        "      Save\n",
+        "    </Button>\n",
+        "  </Card>\n",
+        ");\n",
+    ));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn edit_modes() -> Result<(), Box<dyn std::error::Error>> {
+    let source = concat!(
+        "import { Button } from '@acme/ui';\n",
+        "const a = 1;\n",
+        "const b = 2;\n",
+        "const c = 3;\n",
+        "const d = 4;\n",
+        "const e = 5;\n",
+        "const f = 6;\n",
+        "const g = 7;\n",
+        "export default () => <Button>Save</Button>;\n",
+    );
+
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.tsx").write_str(source)?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--dry-run", "rewrite-tags", "Button", "--rename", "BaseButton"]);
+    cmd.assert().success().stdout(predicate::str::diff(concat!(
+        "--- ./a.tsx\n",
+        "+++ ./a.tsx\n",
+        "@@ -1,4 +1,4 @@\n",
+        "-import { Button } from '@acme/ui';\n",
+        "+import { BaseButton } from '@acme/ui';\n",
+        " const a = 1;\n",
+        " const b = 2;\n",
+        " const c = 3;\n",
+        "@@ -6,4 +6,4 @@\n",
+        " const e = 5;\n",
+        " const f = 6;\n",
+        " const g = 7;\n",
+        "-export default () => <Button>Save</Button>;\n",
+        "+export default () => <BaseButton>Save</BaseButton>;\n",
+    )));
+    dir.child("a.tsx").assert(source);
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--check", "rewrite-tags", "Button", "--rename", "BaseButton"]);
+    cmd.assert().code(1);
+    dir.child("a.tsx").assert(source);
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--check", "rewrite-tags", "Card", "--rename", "BaseCard"]);
+    cmd.assert().success().stdout("");
+
+    // Commands which don't edit files can't be checked
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--check", "tags", "Button"]);
+    cmd.assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains("--check can only be used"));
+
+    let mut cmd = assert_cmd::Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["-i", "rewrite-tags", "Button", "--rename", "BaseButton"]);
+    cmd.write_stdin("n\ny\n");
+    cmd.assert().success();
+    dir.child("a.tsx").assert(
+        source
+            .replace("<Button>Save</Button>", "<BaseButton>Save</BaseButton>"),
+    );
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn config_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("flashlight.toml").write_str(
+        r#"
+format = "quickfix"
+exclude = ["**/*.test.ts"]
+
+[queries]
+moment = ["imports", "moment"]
+"#,
+    )?;
+    dir.child("src/a.ts").write_str("import moment from 'moment'")?;
+    dir.child("src/a.test.ts").write_str("import moment from 'moment'")?;
+
+    // The config file is found in a parent of the working directory
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.child("src").path());
+    cmd.arg("run").arg("moment");
+    cmd.assert().success().stdout(predicate::str::ends_with(
+        "/src/a.ts:1:1: import moment from 'moment'\n",
+    ));
+
+    // Flags override the config file
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.child("src").path());
+    cmd.arg("--format").arg("default").arg("imports").arg("moment");
+    cmd.assert().success().stdout("./a.ts:1:1 import moment from 'moment'\n");
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("run").arg("unknown");
+    cmd.assert().code(2).stderr(predicate::str::contains("Unknown query"));
+
+    dir.child("flashlight.toml").write_str("exclude = [\"**/*.test.ts\",\n")?;
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("imports").arg("moment");
+    cmd.assert().code(2).stderr(predicate::str::contains("line 1"));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn config_through_symlink() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("real/proj/flashlight.toml")
+        .write_str("exclude = [\"gen/**\"]")?;
+    dir.child("real/proj/a.ts").write_str("import moment from 'moment'")?;
+    dir.child("real/proj/gen/b.ts").write_str("import moment from 'moment'")?;
+    std::os::unix::fs::symlink(dir.child("real").path(), dir.child("link"))?;
+
+    // Globs in the config file match when the working directory is a symlink
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.arg("--cwd").arg(dir.child("link/proj").path());
+    cmd.arg("--format").arg("quickfix").arg("imports").arg("moment");
+    cmd.assert().success().stdout(
+        predicate::str::ends_with(
+            "/proj/a.ts:1:1: import moment from 'moment'\n",
+        )
+        .and(predicate::str::contains("gen/b.ts").not()),
+    );
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn config_tsconfig_paths() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("flashlight.toml").write_str(
+        "tsconfig = \"tsconfig.json\"\nentries = [\"src/index.ts\"]",
+    )?;
+    dir.child("tsconfig.json").write_str(
+        r#"{
+  // Comments and trailing commas are allowed
+  "compilerOptions": {
+    "baseUrl": ".",
+    "paths": { "@/*": ["src/*"], },
+  },
+}"#,
+    )?;
+    dir.child("src/index.ts")
+        .write_str("export { a } from '@/a'\nimport { c } from 'lib/c'")?;
+    dir.child("src/a.ts")
+        .write_str("export const a = 1\nexport const b = 2")?;
+    dir.child("lib/c.ts")
+        .write_str("export const c = 1\nexport const d = 2")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.arg("unused-exports");
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./lib/c.ts:2:1 d", "./src/a.ts:2:1 b",
+    )));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn search_paths() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child(".ignore").write_str("generated\n")?;
+    dir.child("src/a.ts").write_str("import a from 'react'")?;
+    dir.child("src/a.test.ts").write_str("import a from 'react'")?;
+    dir.child("lib/b.ts").write_str("import b from 'react'")?;
+    dir.child("test/c.ts").write_str("import c from 'react'")?;
+    dir.child("generated/d.ts").write_str("import d from 'react'")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["imports", "react", "src/a.ts", "lib"]);
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./src/a.ts:1:1 import a from 'react'",
+        "./lib/b.ts:1:1 import b from 'react'",
+    )));
+
+    // The optional specifier is only a search path when the path exists
+    dir.child("test/hooks.ts").write_str("import { useState } from 'react'")?;
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["imports", "react", "useState", "lib", "test"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./test/hooks.ts:1:1 import { useState } from 'react'\n",
+    ));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--path", "lib", "imports", "react", "missing"]);
+    cmd.assert().code(1).stdout("");
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--include", "src/**", "--include", "generated/**"]);
+    cmd.args(["--exclude", "**/*.test.ts", "--no-ignore"]);
+    cmd.args(["imports", "react", "generated", "src", "--path", "test"]);
+    cmd.assert().success().stdout(predicate::str::diff(format!(
+        "{}\n{}\n",
+        "./generated/d.ts:1:1 import d from 'react'",
+        "./src/a.ts:1:1 import a from 'react'",
+    )));
+
+    // Directories can be named like commands
+    dir.child("imports/e.ts").write_str("import e from 'react'")?;
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["search", "import $X from 'react'", "imports"]);
+    cmd.assert().success().stdout(predicate::str::diff(
+        "./imports/e.ts:1:1 import e from 'react' [$X=e]\n",
+    ));
+
+    // Saved queries use the search paths of the command line
+    dir.child("flashlight.toml").write_str(
+        "[queries]\nlib = [\"imports\", \"react\", \"b\", \"lib\"]",
+    )?;
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["run", "lib", "lib"]);
+    cmd.assert().code(2).stderr(predicate::str::contains(
+        "Invalid query lib: Search paths can only be given on the command line",
+    ));
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn changed_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let git = |args: &[&str]| -> Result<(), Box<dyn std::error::Error>> {
+        Command::new("git")
+            .current_dir(dir.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@test.com"])
+            .args(args)
+            .assert()
+            .success();
+        Ok(())
+    };
+
+    dir.child("a.ts")
+        .write_str("import React from 'react'\nimport { b } from './b'")?;
+    dir.child("b.ts").write_str("export const b = 1")?;
+    dir.child("c.ts")
+        .write_str("import _ from 'lodash'\nimport { a } from './a'")?;
+    dir.child("package.json").write_str(
+        r#"{ "dependencies": { "left-pad": "1.0.0", "react": "1.0.0" } }"#,
+    )?;
+    git(&["init", "--quiet"])?;
+    git(&["add", "."])?;
+    git(&["commit", "--quiet", "-m", "Initial commit"])?;
+
+    dir.child("b.ts")
+        .write_str("import React from 'react'\nimport { a } from './a'")?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--changed-since", "HEAD", "imports", "react"]);
+    cmd.assert().success().stdout("./b.ts:1:1 import React from 'react'\n");
+
+    // Unchanged modules are still used to find cycles
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--changed-since", "HEAD", "cycles"]);
+    cmd.assert()
+        .success()
+        .stdout("./b.ts:2:1 cycle 1: import { a } from './a'\n");
+
+    // Only the imports of changed modules are included in the graph
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--changed-since", "HEAD", "graph"]);
+    cmd.assert()
+        .success()
+        .stdout("digraph {\n  \"a.ts\";\n  \"b.ts\" -> \"a.ts\";\n}\n");
+
+    // Dependencies can become unused without their manifest changing
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--changed-since", "HEAD", "deps"]);
+    cmd.assert()
+        .success()
+        .stdout("./package.json:1:21 unused dependency \"left-pad\"\n");
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--staged", "imports", "react"]);
+    cmd.assert().code(1).stdout("");
+
+    git(&["add", "b.ts"])?;
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path());
+    cmd.args(["--staged", "imports", "react"]);
+    cmd.assert().success().stdout("./b.ts:1:1 import React from 'react'\n");
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn watch() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts").write_str("import a from 'react'")?;
+    dir.child("b.ts").write_str("export const b = 1")?;
+
+    let mut child = Command::cargo_bin("flashlight")?
+        .current_dir(dir.path())
+        .args(["--format", "json", "--watch", "imports", "react"])
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+
+    let wait = || std::thread::sleep(std::time::Duration::from_millis(1000));
+
+    wait();
+    dir.child("b.ts").write_str("import b from 'react'")?;
+    wait();
+    dir.child("a.ts").write_str("export const a = 1")?;
+    wait();
+
+    child.kill()?;
+    let output = child.wait_with_output()?;
+    let json = |event: &str, file: &str, text: &str| {
+        format!(
+            "{{\"event\": \"{}\", \"file\": \"{}\", \"line\": 1, \"column\": 1, \"text\": \"{}\"}}",
+            event,
+            dir.child(file).path().canonicalize().unwrap().display(),
+            text
+        )
+    };
+
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        format!(
+            "{}\n{}\n{}\n",
+            json("added", "a.ts", "import a from 'react'"),
+            json("added", "b.ts", "import b from 'react'"),
+            json("removed", "a.ts", "import a from 'react'"),
+        )
+    );
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn watch_graph() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("a.ts").write_str("import { b } from './b'")?;
+    dir.child("b.ts").write_str("export const b = 1")?;
+    dir.child("c.ts").write_str("export const c = 1")?;
+
+    let mut child = Command::cargo_bin("flashlight")?
+        .current_dir(dir.path())
+        .args(["--format", "json", "--watch", "cycles"])
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+
+    let wait = || std::thread::sleep(std::time::Duration::from_millis(1000));
+
+    wait();
+    dir.child("b.ts").write_str("import { c } from './c'")?;
+    dir.child("c.ts").write_str("import { a } from './a'")?;
+    wait();
+    std::fs::remove_file(dir.child("c.ts").path())?;
+    wait();
+
+    child.kill()?;
+    let output = child.wait_with_output()?;
+    let json = |event: &str, file: &str, text: &str| {
+        format!(
+            "{{\"event\": \"{}\", \"file\": \"{}\", \"line\": 1, \"column\": 1, \"text\": \"cycle 1: {}\"}}",
+            event,
+            dir.path().canonicalize().unwrap().join(file).display(),
+            text
+        )
+    };
+
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n",
+            json("added", "a.ts", "import { b } from './b'"),
+            json("added", "b.ts", "import { c } from './c'"),
+            json("added", "c.ts", "import { a } from './a'"),
+            json("removed", "a.ts", "import { b } from './b'"),
+            json("removed", "b.ts", "import { c } from './c'"),
+            json("removed", "c.ts", "import { a } from './a'"),
+        )
+    );
+
+    dir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn lsp() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("flashlight.toml").write_str(concat!(
+        "[[rules]]\nsource = \"moment\"\nmessage = \"Use date-fns\"\n",
+        "[[rules]]\nsource = \"./b\"\nseverity = \"warning\"\n",
+        "message = \"Avoid b\"\n",
+        "[queries]\nbroken = [\"search\", \"foo(\"]\n",
+    ))?;
+    dir.child("src/a.ts").write_str(
+        "import moment from 'moment'\nconst s = '😀'; import { a } from './b'",
+    )?;
+    dir.child("src/b.tsx").write_str("export const a = <Button />")?;
+
+    let uri =
+        |file: &str| format!("file://{}", dir.child(file).path().display());
+    let input: String = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":2,"method":"workspace/symbol","params":{"query":"button"}}"#.to_string(),
+        format!(
+            r#"{{"jsonrpc":"2.0","id":3,"method":"flashlight/importers","params":{{"textDocument":{{"uri":"{}"}}}}}}"#,
+            uri("src/b.tsx")
+        ),
+        r#"{"jsonrpc":"2.0","id":4,"method":"shutdown"}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+    ]
+    .iter()
+    .map(|body| format!("Content-Length: {}\r\n\r\n{}", body.len(), body))
+    .collect();
+
+    let mut cmd = assert_cmd::Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path()).arg("lsp");
+    cmd.write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            r#"{{"jsonrpc":"2.0","method":"textDocument/publishDiagnostics","params":{{"diagnostics":[{{"code":"check","message":"error: Use date-fns: import moment from 'moment'","range":{{"end":{{"character":27,"line":0}},"start":{{"character":0,"line":0}}}},"severity":1,"source":"flashlight"}},{{"code":"check","message":"warning: Avoid b: const s = '😀'; import {{ a }} from './b'","range":{{"end":{{"character":39,"line":1}},"start":{{"character":17,"line":1}}}},"severity":2,"source":"flashlight"}}],"uri":"{}"}}}}"#,
+            uri("src/a.ts")
+        )))
+        // Queries which fail are shown without stopping the server
+        .stdout(predicate::str::contains(
+            r#"{"jsonrpc":"2.0","method":"window/showMessage","params":{"message":"Query broken failed: Invalid pattern: "#,
+        ))
+        .stdout(predicate::str::contains(format!(
+            r#"{{"id":2,"jsonrpc":"2.0","result":[{{"kind":5,"location":{{"range":{{"end":{{"character":27,"line":0}},"start":{{"character":17,"line":0}}}},"uri":"{}"}},"name":"<Button>"}}]}}"#,
+            uri("src/b.tsx")
+        )))
+        // Columns are counted in UTF-16 code units
+        .stdout(predicate::str::contains(format!(
+            r#"{{"id":3,"jsonrpc":"2.0","result":[{{"range":{{"end":{{"character":39,"line":1}},"start":{{"character":17,"line":1}}}},"uri":"{}"}}]}}"#,
+            uri("src/a.ts")
+        )));
+
+    let mut cmd = Command::cargo_bin("flashlight")?;
+    cmd.current_dir(dir.path()).args(["--watch", "lsp"]);
+    cmd.assert().code(2).stderr(predicate::str::contains("--watch"));
+
+    dir.close().unwrap();
+    Ok(())
+}